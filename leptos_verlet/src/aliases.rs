@@ -3,9 +3,13 @@ use leptos_bevy_canvas::prelude::{LeptosEventReceiver, LeptosEventSender};
 use crate::{
     plugins::{
         asset_loader::plugin::LoadModelEvent,
+        collider::plugin::SpawnCollider,
+        field::plugin::{ForceFieldRequest, GravityZoneRequest},
         info::plugin::{PointInfo, SetPointInfo},
         modification::plugin::{ModificationTarget, ModifyEventType},
+        muscle::plugin::SetMuscleTarget,
         play_state::plugin::SimulationPlayStateRequest,
+        simulation::plugin::{PointDespawned, StickBroken},
     },
     prelude::{LeptosResize, SpawnRequest},
 };
@@ -14,7 +18,13 @@ pub type ModificationTargetSender = LeptosEventSender<ModificationTarget>;
 pub type ModificationEventSender = LeptosEventSender<ModifyEventType>;
 pub type PointInfoReceiver = LeptosEventReceiver<PointInfo>;
 pub type PointInfoSender = LeptosEventSender<SetPointInfo>;
+pub type StickBrokenReceiver = LeptosEventReceiver<StickBroken>;
+pub type PointDespawnedReceiver = LeptosEventReceiver<PointDespawned>;
 pub type PlayStateSender = LeptosEventSender<SimulationPlayStateRequest>;
 pub type SpawnSender = LeptosEventSender<SpawnRequest>;
+pub type ColliderSender = LeptosEventSender<SpawnCollider>;
+pub type GravityZoneSender = LeptosEventSender<GravityZoneRequest>;
+pub type ForceFieldSender = LeptosEventSender<ForceFieldRequest>;
+pub type MuscleTargetSender = LeptosEventSender<SetMuscleTarget>;
 pub type ContainerSizeSender = LeptosEventSender<LeptosResize>;
 pub type AssetSender = LeptosEventSender<LoadModelEvent>;
@@ -8,6 +8,8 @@ pub struct LeptosResize {
     pub height: f32,
 }
 
+/// Keeps the width of the bounds matching the container's aspect ratio.
+/// Every bounds shape is sized from these bounds, so they follow the container as well.
 pub fn window_listener(
     mut window_event: EventReader<LeptosResize>,
     mut sim_settings: ResMut<SimulationSettings>,
@@ -21,3 +23,265 @@ pub fn window_listener(
         sim_settings.simulation_bounds.x.1 = sim_width;
     }
 }
+
+/// The most vertices a `BoundsPolygon` can have.
+pub const MAX_BOUNDS_POLYGON_VERTICES: usize = 16;
+
+/// The outline the points are kept inside of.
+///
+/// Every shape is sized from the container, so it stretches along with the canvas.
+/// The flat shapes bound the x and y axes and leave the z axis to the z bounds.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum BoundsShape {
+    /// Walls at the container's edges and a floor at y=0.
+    #[default]
+    Rectangle,
+    /// The largest circle that fits in the container.
+    Circle,
+    /// The largest sphere that fits in the container. Replaces the z bounds as well.
+    Sphere,
+    /// The container with its corners rounded off.
+    RoundedRectangle {
+        /// The radius of the corners as a fraction of the container's shorter side (0 to 0.5).
+        corner_radius: f32,
+    },
+    ConvexPolygon(BoundsPolygon),
+}
+impl BoundsShape {
+    /// Finds how far outside the shape a position is when the container is `width` by `height`.
+    ///
+    /// Returns the direction back into the shape and the distance the position has to be moved,
+    /// or `None` if the position is inside. Always `None` for `Rectangle`, which is handled by
+    /// the individual axis bounds.
+    pub fn contact(&self, position: Vec3, width: f32, height: f32) -> Option<(Vec3, f32)> {
+        let center = Vec3::new(0., height * 0.5, 0.);
+        let shorter_side = width.min(height);
+
+        match *self {
+            BoundsShape::Rectangle => None,
+            BoundsShape::Circle => {
+                let offset = (position - center).truncate();
+                inside_radius(offset, shorter_side * 0.5)
+                    .map(|(normal, depth)| (normal.extend(0.), depth))
+            }
+            BoundsShape::Sphere => {
+                let offset = position - center;
+                let distance = offset.length();
+                let radius = shorter_side * 0.5;
+                (distance > radius).then(|| (-offset / distance, distance - radius))
+            }
+            BoundsShape::RoundedRectangle { corner_radius } => {
+                let radius = corner_radius.clamp(0., 0.5) * shorter_side;
+                let inner = Vec2::new(width * 0.5 - radius, height * 0.5 - radius).max(Vec2::ZERO);
+
+                // Measure from the rectangle the corner circles are centered on
+                let offset = (position - center).truncate();
+                let closest = offset.clamp(-inner, inner);
+                inside_radius(offset - closest, radius)
+                    .map(|(normal, depth)| (normal.extend(0.), depth))
+            }
+            BoundsShape::ConvexPolygon(polygon) => polygon
+                .contact(position.truncate(), width, height)
+                .map(|(normal, depth)| (normal.extend(0.), depth)),
+        }
+    }
+}
+
+/// The direction back towards the origin and the distance past `radius` of an offset,
+/// or `None` if the offset is within the radius.
+fn inside_radius(offset: Vec2, radius: f32) -> Option<(Vec2, f32)> {
+    let distance = offset.length();
+    (distance > radius).then(|| (-offset / distance, distance - radius))
+}
+
+/// A convex outline with up to `MAX_BOUNDS_POLYGON_VERTICES` vertices.
+///
+/// Vertices are fractions of the container: x runs from -0.5 (left edge) to 0.5 (right edge)
+/// and y from 0 (the floor) to 1 (the top). Either winding order can be used.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundsPolygon {
+    vertices: [Vec2; MAX_BOUNDS_POLYGON_VERTICES],
+    len: usize,
+}
+impl BoundsPolygon {
+    /// Creates a polygon from its vertices, or `None` if there are fewer than 3 or more than
+    /// `MAX_BOUNDS_POLYGON_VERTICES` of them, or they don't outline a convex shape with an area.
+    pub fn new(vertices: &[Vec2]) -> Option<Self> {
+        if !(3..=MAX_BOUNDS_POLYGON_VERTICES).contains(&vertices.len())
+            || vertices.iter().any(|vertex| !vertex.is_finite())
+        {
+            return None;
+        }
+
+        // Every corner of a convex polygon turns the same way, going around exactly once
+        let turns: Vec<f32> = (0..vertices.len())
+            .map(|i| {
+                let previous = vertices[(i + vertices.len() - 1) % vertices.len()];
+                let next = vertices[(i + 1) % vertices.len()];
+                (vertices[i] - previous).angle_to(next - vertices[i])
+            })
+            .collect();
+        let left = turns.iter().any(|&turn| turn > f32::EPSILON);
+        let right = turns.iter().any(|&turn| turn < -f32::EPSILON);
+        let total: f32 = turns.iter().sum();
+        if left == right || (total.abs() - std::f32::consts::TAU).abs() > 1e-3 {
+            return None;
+        }
+
+        let mut polygon = Self {
+            vertices: [Vec2::ZERO; MAX_BOUNDS_POLYGON_VERTICES],
+            len: vertices.len(),
+        };
+        polygon.vertices[..vertices.len()].copy_from_slice(vertices);
+        Some(polygon)
+    }
+
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices[..self.len]
+    }
+
+    /// Finds how far outside the polygon a position is when the container is `width` by `height`.
+    fn contact(&self, position: Vec2, width: f32, height: f32) -> Option<(Vec2, f32)> {
+        let scale = Vec2::new(width, height);
+        let vertices = self.vertices();
+
+        // Positive when the vertices run counter-clockwise
+        let winding: f32 = (0..vertices.len())
+            .map(|i| vertices[i].perp_dot(vertices[(i + 1) % vertices.len()]))
+            .sum::<f32>()
+            .signum();
+
+        let mut outside = false;
+        let mut closest: Option<(Vec2, f32)> = None;
+        for i in 0..vertices.len() {
+            let start = vertices[i] * scale;
+            let end = vertices[(i + 1) % vertices.len()] * scale;
+            let edge = end - start;
+
+            // Inside is on the left of every edge of a counter-clockwise polygon
+            if edge.perp_dot(position - start) * winding < 0. {
+                outside = true;
+            }
+
+            let along = ((position - start).dot(edge) / edge.length_squared().max(f32::EPSILON))
+                .clamp(0., 1.);
+            let on_edge = start + edge * along;
+            let distance = on_edge.distance(position);
+            if closest.is_none_or(|(_, best)| distance < best) {
+                closest = Some((on_edge, distance));
+            }
+        }
+
+        if !outside {
+            return None;
+        }
+        let (on_edge, distance) = closest?;
+        let normal = (on_edge - position).try_normalize()?;
+        Some((normal, distance))
+    }
+}
+
+/// What happens to a point that reaches a side of the bounds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BoundaryMode {
+    /// The point bounces off with `coeff_restitution` of its speed.
+    #[default]
+    Bounce,
+    /// The point stops dead against the side.
+    Sticky,
+    /// The point leaves through this side and comes back in through the opposite one
+    /// keeping its velocity. Acts like `Bounce` on the non-rectangular outlines.
+    Wrap,
+    /// The point keeps sliding along the side but loses all of its speed into it.
+    Absorb,
+    /// The point is removed from the simulation.
+    Despawn,
+    /// There is no side, the point passes through.
+    Open,
+}
+
+/// The `BoundaryMode` of each side of the bounds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BoundaryModes {
+    /// The wall at -x.
+    pub left: BoundaryMode,
+    /// The wall at +x.
+    pub right: BoundaryMode,
+    /// The floor at y=0.
+    pub bottom: BoundaryMode,
+    /// The ceiling at the top of the container.
+    pub top: BoundaryMode,
+    /// The wall at -z.
+    pub back: BoundaryMode,
+    /// The wall at +z.
+    pub front: BoundaryMode,
+    /// The outline of any `BoundsShape` other than `Rectangle`.
+    pub outline: BoundaryMode,
+}
+impl Default for BoundaryModes {
+    fn default() -> Self {
+        Self {
+            left: BoundaryMode::Bounce,
+            right: BoundaryMode::Bounce,
+            bottom: BoundaryMode::Bounce,
+            top: BoundaryMode::Open,
+            back: BoundaryMode::Bounce,
+            front: BoundaryMode::Bounce,
+            outline: BoundaryMode::Bounce,
+        }
+    }
+}
+impl BoundaryModes {
+    /// Every side, including the ceiling, behaves the same.
+    pub fn all(mode: BoundaryMode) -> Self {
+        Self {
+            left: mode,
+            right: mode,
+            bottom: mode,
+            top: mode,
+            back: mode,
+            front: mode,
+            outline: mode,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_polygon_rejects_bad_outlines() {
+        let square = [
+            Vec2::new(-0.5, 0.),
+            Vec2::new(0.5, 0.),
+            Vec2::new(0.5, 1.),
+            Vec2::new(-0.5, 1.),
+        ];
+        assert!(BoundsPolygon::new(&square).is_some());
+        // Either winding works
+        let mut clockwise = square;
+        clockwise.reverse();
+        assert!(BoundsPolygon::new(&clockwise).is_some());
+
+        // Too few or too many vertices
+        assert!(BoundsPolygon::new(&square[..2]).is_none());
+        assert!(BoundsPolygon::new(&[Vec2::ZERO; MAX_BOUNDS_POLYGON_VERTICES + 1]).is_none());
+        // No area
+        assert!(BoundsPolygon::new(&[Vec2::ZERO, Vec2::X, Vec2::X * 2.]).is_none());
+        // Dented
+        let dented = [
+            Vec2::new(-0.5, 0.),
+            Vec2::new(0.5, 0.),
+            Vec2::new(0., 0.5),
+            Vec2::new(0.5, 1.),
+            Vec2::new(-0.5, 1.),
+        ];
+        assert!(BoundsPolygon::new(&dented).is_none());
+        // Crossing itself
+        let star: Vec<Vec2> = (0..5)
+            .map(|i| Vec2::from_angle(i as f32 * 2. * std::f32::consts::TAU / 5.))
+            .collect();
+        assert!(BoundsPolygon::new(&star).is_none());
+    }
+}
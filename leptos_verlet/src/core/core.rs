@@ -8,12 +8,15 @@ use crate::{
     plugins::{
         asset_loader::plugin::{AssetLoaderPlugin, LoadModelEvent},
         attachment::plugin::AttachmentPlugin,
+        collider::plugin::{ColliderPlugin, SpawnCollider},
+        field::plugin::{FieldPlugin, ForceFieldRequest, GravityZoneRequest},
         info::plugin::{InfoPlugin, PointInfo, SetPointInfo},
         modification::plugin::ModificationPlugin,
+        muscle::plugin::{MusclePlugin, SetMuscleTarget},
         play_state::plugin::PlayStatePlugin,
         render::plugin::RenderPlugin,
         schedule::plugin::SchedulePlugin,
-        simulation::plugin::SimulationPlugin,
+        simulation::plugin::{PointDespawned, SimulationPlugin, StickBroken},
         start_up::plugin::StartupPlugin,
     },
     prelude::{
@@ -30,9 +33,15 @@ pub fn VerletConfigProvider(
     let (event_sender, bevy_event_receiver) = event_l2b::<ModifyEventType>();
     let (element_size_sender, bevy_element_size_receiver) = event_l2b::<LeptosResize>();
     let (spawn_sender, bevy_spawn_receiver) = event_l2b::<SpawnRequest>();
+    let (collider_sender, bevy_collider_receiver) = event_l2b::<SpawnCollider>();
+    let (gravity_zone_sender, bevy_gravity_zone_receiver) = event_l2b::<GravityZoneRequest>();
+    let (force_field_sender, bevy_force_field_receiver) = event_l2b::<ForceFieldRequest>();
+    let (muscle_target_sender, bevy_muscle_target_receiver) = event_l2b::<SetMuscleTarget>();
 
     let (info_receiver, bevy_info_sender) = event_b2l::<PointInfo>();
     let (info_sender, bevy_info_receiver) = event_l2b::<SetPointInfo>();
+    let (stick_broken_receiver, bevy_stick_broken_sender) = event_b2l::<StickBroken>();
+    let (point_despawned_receiver, bevy_point_despawned_sender) = event_b2l::<PointDespawned>();
 
     let (asset_sender, bevy_asset_receiver) = event_l2b::<LoadModelEvent>();
 
@@ -41,8 +50,14 @@ pub fn VerletConfigProvider(
     provide_context(event_sender);
     provide_context(element_size_sender);
     provide_context(spawn_sender);
+    provide_context(collider_sender);
+    provide_context(gravity_zone_sender);
+    provide_context(force_field_sender);
+    provide_context(muscle_target_sender);
     provide_context(info_receiver);
     provide_context(info_sender);
+    provide_context(stick_broken_receiver);
+    provide_context(point_despawned_receiver);
     provide_context(asset_sender);
 
     provide_context(bevy_state_receiver);
@@ -50,8 +65,14 @@ pub fn VerletConfigProvider(
     provide_context(bevy_event_receiver);
     provide_context(bevy_element_size_receiver);
     provide_context(bevy_spawn_receiver);
+    provide_context(bevy_collider_receiver);
+    provide_context(bevy_gravity_zone_receiver);
+    provide_context(bevy_force_field_receiver);
+    provide_context(bevy_muscle_target_receiver);
     provide_context(bevy_info_sender);
     provide_context(bevy_info_receiver);
+    provide_context(bevy_stick_broken_sender);
+    provide_context(bevy_point_despawned_sender);
     provide_context(bevy_asset_receiver);
 
     provide_context(simulation_settings);
@@ -74,8 +95,14 @@ pub fn VerletCanvas(parent_element: NodeRef<leptos::html::Div>) -> impl IntoView
     let bevy_event_receiver = expect_context::<BevyEventReceiver<ModifyEventType>>();
     let bevy_element_size_receiver = expect_context::<BevyEventReceiver<LeptosResize>>();
     let bevy_spawn_receiver = expect_context::<BevyEventReceiver<SpawnRequest>>();
+    let bevy_collider_receiver = expect_context::<BevyEventReceiver<SpawnCollider>>();
+    let bevy_gravity_zone_receiver = expect_context::<BevyEventReceiver<GravityZoneRequest>>();
+    let bevy_force_field_receiver = expect_context::<BevyEventReceiver<ForceFieldRequest>>();
+    let bevy_muscle_target_receiver = expect_context::<BevyEventReceiver<SetMuscleTarget>>();
     let bevy_info_sender = expect_context::<BevyEventSender<PointInfo>>();
     let bevy_info_receiver = expect_context::<BevyEventReceiver<SetPointInfo>>();
+    let bevy_stick_broken_sender = expect_context::<BevyEventSender<StickBroken>>();
+    let bevy_point_despawned_sender = expect_context::<BevyEventSender<PointDespawned>>();
     let bevy_asset_receiver = expect_context::<BevyEventReceiver<LoadModelEvent>>();
 
     let UseElementSizeReturn { width, height } = use_element_size(parent_element);
@@ -96,8 +123,14 @@ pub fn VerletCanvas(parent_element: NodeRef<leptos::html::Div>) -> impl IntoView
                     bevy_event_receiver,
                     bevy_element_size_receiver,
                     bevy_spawn_receiver,
+                    bevy_collider_receiver,
+                    bevy_gravity_zone_receiver,
+                    bevy_force_field_receiver,
+                    bevy_muscle_target_receiver,
                     bevy_info_sender,
                     bevy_info_receiver,
+                    bevy_stick_broken_sender,
+                    bevy_point_despawned_sender,
                     bevy_asset_receiver,
                     simulation_settings
                 )
@@ -112,8 +145,14 @@ fn init_bevy_app(
     event_receiver: BevyEventReceiver<ModifyEventType>,
     window_resize_receiver: BevyEventReceiver<LeptosResize>,
     spawn_receiver: BevyEventReceiver<SpawnRequest>,
+    collider_receiver: BevyEventReceiver<SpawnCollider>,
+    gravity_zone_receiver: BevyEventReceiver<GravityZoneRequest>,
+    force_field_receiver: BevyEventReceiver<ForceFieldRequest>,
+    muscle_target_receiver: BevyEventReceiver<SetMuscleTarget>,
     info_sender: BevyEventSender<PointInfo>,
     info_receiver: BevyEventReceiver<SetPointInfo>,
+    stick_broken_sender: BevyEventSender<StickBroken>,
+    point_despawned_sender: BevyEventSender<PointDespawned>,
     asset_receiver: BevyEventReceiver<LoadModelEvent>,
     simulation_settings: SimulationSettings,
 ) -> App {
@@ -135,8 +174,14 @@ fn init_bevy_app(
         .import_event_from_leptos(event_receiver)
         .import_event_from_leptos(window_resize_receiver)
         .import_event_from_leptos(spawn_receiver)
+        .import_event_from_leptos(collider_receiver)
+        .import_event_from_leptos(gravity_zone_receiver)
+        .import_event_from_leptos(force_field_receiver)
+        .import_event_from_leptos(muscle_target_receiver)
         .export_event_to_leptos(info_sender)
         .import_event_from_leptos(info_receiver)
+        .export_event_to_leptos(stick_broken_sender)
+        .export_event_to_leptos(point_despawned_sender)
         .import_event_from_leptos(asset_receiver)
         .insert_resource(ClearColor(Color::NONE))
         .insert_resource(simulation_settings)
@@ -145,6 +190,9 @@ fn init_bevy_app(
         .add_plugins(ModificationPlugin)
         .add_plugins(InfoPlugin)
         .add_plugins(SimulationPlugin)
+        .add_plugins(ColliderPlugin)
+        .add_plugins(FieldPlugin)
+        .add_plugins(MusclePlugin)
         .add_plugins(StartupPlugin)
         .add_plugins(RenderPlugin)
         .add_plugins(AttachmentPlugin);
@@ -1,4 +1,5 @@
 pub mod container_bounds;
 pub mod core;
 pub mod parameters;
+pub mod solver;
 pub mod spawner;
@@ -2,7 +2,11 @@ use bevy::prelude::*;
 use once_cell::sync::Lazy;
 
 use crate::{
-    core::spawner::{SpawnNode, spawner},
+    core::{
+        container_bounds::{BoundaryModes, BoundsShape},
+        solver::kinematic::PathLooping,
+        spawner::{SpawnNode, spawner},
+    },
     prelude::{MaterialType, MeshType},
 };
 
@@ -10,11 +14,34 @@ use crate::{
 pub const CAMERA_FOV: f32 = std::f32::consts::PI / 4.;
 pub const CAMERA_DISTANCE: f32 = 4.; // m
 pub static HALF_CAMERA_HEIGHT: Lazy<f32> = Lazy::new(|| CAMERA_DISTANCE * (CAMERA_FOV / 2.0).tan());
+/// The tick rate the per-tick loss factors (air resistance) are expressed at.
+/// Losses are rescaled from this rate so changing the tick rate or substeps doesn't change the feel.
+pub const DAMPING_REFERENCE_RATE: f32 = 120.; // Hz
+/// The most refinements made to the rotation of a `ShapeMatch` each time it's matched.
+/// Starting from the last match, the rotation usually settles in a couple of them.
+pub const SHAPE_MATCH_ITERATIONS: u32 = 10;
 
 #[derive(Resource, Copy, Clone, Debug)]
 pub struct SimulationSettings {
+    /// The number of physics ticks simulated per second, independent of the rendered frame rate.
+    ///
+    /// Units are Hz
+    pub tick_rate: f32,
+    /// The number of substeps each physics tick is split into.
+    /// More substeps give stiffer, more stable bodies at the cost of more computation.
+    pub substeps: u16,
+    /// The maximum number of physics ticks that will be simulated in a single rendered frame.
+    /// When a frame takes longer than this many ticks, the remaining time is dropped and the
+    /// simulation slows down instead of falling further and further behind.
+    pub max_ticks_per_frame: u16,
+    /// The seed for the random number generator used to order the solver.
+    ///
+    /// With a seed, identical spawn requests produce bit-identical trajectories, which allows
+    /// replays and regression tests. Without one, every run is seeded differently.
+    pub seed: Option<u64>,
     /// The number of iterations to converge the sticks to their proper positions.
-    /// Higher values will result in less elasticity in the simulation bodies.
+    /// Higher values will result in less elasticity in rigid sticks, the stiffness of
+    /// compliant sticks is set by their own compliance instead.
     pub converge_iterations: u16,
     /// A value to optimize rendering. Indicates the minimum distace the simulation points
     /// must travel in order to update the rendering. If any simulation point travels this distance
@@ -33,20 +60,66 @@ pub struct SimulationSettings {
     ///
     /// Units are meters
     pub default_geometry_stick_size: f32,
+    /// The compliance of the bends the builtin geometries (cloth, rope, ...) add along their chains
+    /// to resist folding. No bends are added when not specified.
+    ///
+    /// Units are rad/(N·m)
+    pub default_geometry_bend_compliance: Option<f32>,
     /// The distance from the mouse any modification request will disperse to nearby simulation bodies.
     pub interaction_radius: f32,
-    /// Percent of energy kept after each contact with a collision surface.
+    /// Percent of energy kept after each contact with a collision surface or another point.
     pub coeff_restitution: f32,
-    /// Percent of energy kept after each contact with the floor.
-    /// A rolling/sliding (rolling isn't really simulated) object is in constant contact with the floor
-    /// so this value is appied at every every frame - a little goes a long way.
-    pub friction_restituation: f32,
-    /// m/s^2
-    pub gravity: f32,
-    /// The amount of energy kept each frame a simulation point passes through the air.
-    /// As this loss is applied every frame, a little goes a long way.
+    /// Should points collide with each other using their radius.
+    /// Points connected by a stick or a spring never collide with each other.
+    pub point_collisions: bool,
+    /// Should sticks collide with points and with each other using their radius.
+    /// Sticks sharing a point never collide with each other.
+    pub stick_collisions: bool,
+    /// Should points be swept from where they were to where they moved each substep,
+    /// so fast points can't pass through the bounds or thin colliders between two substeps.
+    pub continuous_collision: bool,
+    /// Should bodies that stay still go to sleep to save on computation.
+    /// A body is every point connected through sticks, springs, pressures and shape matches.
+    /// Bodies with muscles or kinematic paths never sleep.
+    pub sleeping: bool,
+    /// The speed every point of a body has to stay under for it to fall asleep.
+    ///
+    /// Units are m/s
+    pub sleep_speed: f32,
+    /// How long a body has to stay under the `sleep_speed` before it falls asleep.
+    ///
+    /// Units are seconds
+    pub sleep_delay: f32,
+    /// The width of the cells used to bucket points and sticks when looking for collisions
+    /// and for the points/sticks under the mouse.
+    /// Roughly the size of the largest point works well.
+    ///
+    /// Units are m
+    pub spatial_hash_cell_size: f32,
+    /// The coefficient of static friction between points and the bounds.
+    /// A point resting against a side won't start sliding until the push along the side
+    /// is larger than this times the push into it.
+    pub static_friction: f32,
+    /// The coefficient of kinetic friction between points and the bounds.
+    /// Slows a point sliding along a side in proportion to how hard it's pushed into it.
+    pub kinetic_friction: f32,
+    /// The acceleration every point feels outside of any gravity zone.
+    ///
+    /// Units are m/s^2
+    pub gravity: Vec3,
+    /// The amount of energy kept each tick a simulation point passes through the air.
+    /// As this loss is applied every tick, a little goes a long way.
+    ///
+    /// Expressed per tick at the `DAMPING_REFERENCE_RATE`.
     pub air_resistance: f32,
+    /// The density of the air panels push against.
+    ///
+    /// Units are kg/m^3
+    pub air_density: f32,
     pub simulation_bounds: SimulationBounds,
+    /// Any point that leaves this volume is despawned, regardless of the bounds.
+    /// Keeps runaway points from overflowing the simulation.
+    pub kill_volume: KillVolume,
     /// A value from 0-1 that is applied to filter out harsh velocity changes
     /// and can also be used to smooth out any "jitters" in a simulation body.
     ///
@@ -73,17 +146,32 @@ impl Default for SimulationSettings {
         let light_position = Vec3::new(10., 10., 10.);
 
         Self {
+            tick_rate: 120.,
+            substeps: 1,
+            max_ticks_per_frame: 8,
+            seed: None,
             converge_iterations: 10,
             min_render_delta: 0.0,
             max_unchanged_frames: 120,
             default_geometry_point_size: 0.025,
             default_geometry_stick_size: 0.01,
+            default_geometry_bend_compliance: Some(0.01),
             interaction_radius: 0.03,
             coeff_restitution: 0.95,
-            friction_restituation: 0.95,
-            gravity: 9.8,
+            point_collisions: false,
+            stick_collisions: false,
+            continuous_collision: true,
+            sleeping: false,
+            sleep_speed: 0.01,
+            sleep_delay: 1.,
+            spatial_hash_cell_size: 0.1,
+            static_friction: 0.5,
+            kinetic_friction: 0.3,
+            gravity: Vec3::new(0., -9.8, 0.),
             air_resistance: 0.995,
+            air_density: 1.2,
             simulation_bounds: SimulationBounds::new(true, true, true),
+            kill_volume: KillVolume::default(),
             jerk_damping: 0.4,
             camera_fov,
             camera_position,
@@ -98,6 +186,20 @@ impl Default for SimulationSettings {
         }
     }
 }
+impl SimulationSettings {
+    /// The duration of a single physics tick.
+    ///
+    /// Units are seconds
+    pub fn tick_delta(&self) -> f32 {
+        1. / self.tick_rate.max(f32::EPSILON)
+    }
+    /// The duration of a single substep of a physics tick.
+    ///
+    /// Units are seconds
+    pub fn substep_delta(&self) -> f32 {
+        self.tick_delta() / self.substeps.max(1) as f32
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 /// The bound value is calculated as an event based on the container size.
@@ -106,6 +208,12 @@ pub struct SimulationBounds {
     pub x: (bool, f32),
     pub y: (bool, f32),
     pub z: (bool, f32),
+    /// The outline the points are kept inside of, sized from the x and y bounds.
+    /// Any shape other than `Rectangle` is always enforced, the x and y flags only toggle
+    /// the walls and floor of the rectangle.
+    pub shape: BoundsShape,
+    /// What happens to points reaching each side of the bounds.
+    pub modes: BoundaryModes,
 }
 impl SimulationBounds {
     pub fn new(x: bool, y: bool, z: bool) -> Self {
@@ -113,8 +221,44 @@ impl SimulationBounds {
             x: (x, *HALF_CAMERA_HEIGHT * 2.),
             y: (y, *HALF_CAMERA_HEIGHT * 2.),
             z: (z, CAMERA_DISTANCE),
+            shape: BoundsShape::Rectangle,
+            modes: BoundaryModes::default(),
         }
     }
+
+    pub fn with_modes(mut self, modes: BoundaryModes) -> Self {
+        self.modes = modes;
+        self
+    }
+
+    pub fn with_shape(mut self, shape: BoundsShape) -> Self {
+        self.shape = shape;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// An axis aligned box the simulation is allowed to run in.
+pub struct KillVolume {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+impl Default for KillVolume {
+    fn default() -> Self {
+        Self {
+            min: Vec3::splat(-5_000.),
+            max: Vec3::splat(5_000.),
+        }
+    }
+}
+impl KillVolume {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
 }
 
 #[derive(Component, Clone, Copy, Debug, PartialEq)]
@@ -129,12 +273,33 @@ pub struct Point {
     pub rendered_position: Vec3,
     /// The position the point was at on the previous frame
     pub previously_rendered_position: Vec3,
+    /// The position the point was at one tick before `prev_position`.
+    /// Used to filter out acceleration spikes between ticks.
+    pub last_tick_position: Vec3,
     /// Any fixed external forces that should act on the point (magnetic field, applied forces, ...).
     /// The forces act relative to the world coordinate system.
     ///
     /// Important: Gravity is applied at a simulation level. Gravity can be turned off if desired and
     /// only added to specific points.
+    ///
+    /// Units are N, the resulting acceleration is scaled by the point's mass.
     pub external_forces: Vec3,
+    /// The mass of the point. Heavier points are moved less when resolving constraints
+    /// and locked points behave as if their mass was infinite.
+    ///
+    /// Units are kg
+    pub mass: f32,
+    /// The radius of the point's collision sphere. Points without a radius never collide with each other.
+    /// Spawned points take their radius from the `point_size` of their `SpawnNode`.
+    ///
+    /// Units are meters
+    pub radius: f32,
+    /// Is the point asleep. Sleeping points aren't simulated until something wakes their body.
+    pub asleep: bool,
+    /// How long the point has been moving slower than the `sleep_speed`.
+    ///
+    /// Units are seconds
+    pub calm_time: f32,
 }
 impl Point {
     pub fn new(position: Vec3, prev_position: Vec3, locked: bool) -> Self {
@@ -144,7 +309,12 @@ impl Point {
             locked,
             rendered_position: position,
             previously_rendered_position: position,
+            last_tick_position: prev_position,
             external_forces: Vec3::ZERO,
+            mass: 1.,
+            radius: 0.,
+            asleep: false,
+            calm_time: 0.,
         }
     }
     pub fn new_with_options(
@@ -159,19 +329,54 @@ impl Point {
             locked,
             rendered_position: position,
             previously_rendered_position: position,
+            last_tick_position: prev_position,
             external_forces,
+            mass: 1.,
+            radius: 0.,
+            asleep: false,
+            calm_time: 0.,
         }
     }
+    /// Sets the mass of the point.
+    pub fn with_mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
 
-    pub fn update_properties(&mut self, time: &Res<Time>, sim_settings: &SimulationSettings) {
-        let velocity = self.calculate_affected_velocity(sim_settings);
+    /// Wakes the point up, along with the rest of its body on the next tick.
+    /// Call this after moving a point or changing its `external_forces` by hand.
+    pub fn wake(&mut self) {
+        self.asleep = false;
+        self.calm_time = 0.;
+    }
 
-        // new position = pos + vel + ½·a·dt²
-        let dt = time.delta_secs();
-        let acc = Vec3::new(0.0, -sim_settings.gravity, 0.0) + self.external_forces;
-        let new_pos = self.position + velocity + acc * dt * (1. / 120.);
+    /// The inverse of the point's mass, which is how much of a shared correction it takes.
+    /// Locked points, and points without a positive mass, are treated as infinitely heavy.
+    pub fn inverse_mass(&self) -> f32 {
+        if self.locked || self.mass <= 0. {
+            0.
+        } else {
+            1. / self.mass
+        }
+    }
+
+    /// Advances the point by a single step of `dt` seconds under the `gravity` felt at its position
+    /// and any `field_forces` (N) acting on it on top of its `external_forces`.
+    pub fn update_properties(
+        &mut self,
+        dt: f32,
+        gravity: Vec3,
+        field_forces: Vec3,
+        sim_settings: &SimulationSettings,
+    ) {
+        let velocity = self.calculate_affected_velocity(dt, sim_settings);
+
+        // new position = pos + vel + a·dt²
+        let acc = gravity + (self.external_forces + field_forces) * self.inverse_mass();
+        let new_pos = self.position + velocity + acc * dt * dt;
 
-        // shift “current” into “previous” for the next frame
+        // shift “current” into “previous” for the next step
+        self.last_tick_position = self.prev_position;
         self.prev_position = self.position;
         self.position = new_pos;
     }
@@ -182,17 +387,14 @@ impl Point {
     }
 
     /// Calculates the velocity according to outside factors such as air
-    /// resistance and friction.
-    pub fn calculate_affected_velocity(self, sim_settings: &SimulationSettings) -> Vec3 {
-        let mut new_velocity = (self.position - self.prev_position) * sim_settings.air_resistance;
+    /// resistance over a step of `dt` seconds.
+    pub fn calculate_affected_velocity(self, dt: f32, sim_settings: &SimulationSettings) -> Vec3 {
+        // Rescale the per-tick losses so they're independent of the step size.
+        let steps = dt * DAMPING_REFERENCE_RATE;
+        let air_resistance = sim_settings.air_resistance.powf(steps);
 
-        if self.position[1] <= 0.001 {
-            // Calculate the change in velocity due to friction losses.
-            let current_velocity = self.calculate_velocity();
-            new_velocity = current_velocity * sim_settings.friction_restituation;
-        }
-
-        new_velocity
+        // Friction is applied by the contacts themselves
+        (self.position - self.prev_position) * air_resistance
     }
 
     pub fn spawn(
@@ -215,18 +417,718 @@ impl Point {
     }
 }
 
-#[derive(Component)]
-pub struct Stick {
-    pub point1: Entity,
-    pub point2: Entity,
+/// A rigid connection between two points.
+///
+/// Inside the ECS the endpoints are the point entities, inside a `VerletWorld` they are
+/// the indices of the points.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Stick<P = Entity> {
+    pub point1: P,
+    pub point2: P,
     pub length: f32,
+    /// How much the stick gives under load, the inverse of its stiffness.
+    /// A value of 0 is a rigid stick, larger values give stretchier sticks.
+    ///
+    /// Unlike `converge_iterations`, the stiffness this gives doesn't depend on the
+    /// number of iterations or the tick rate.
+    ///
+    /// Units are m/N
+    pub compliance: f32,
+    /// Which directions the stick resists being pushed or pulled in.
+    pub kind: StickKind,
+    /// The strain (stretch relative to `length`) at which the stick breaks and is removed
+    /// from the simulation. Unbreakable when not specified.
+    pub max_strain: Option<f32>,
+    /// The radius of the stick's collision capsule. Sticks without a radius are never solid.
+    /// Spawned sticks take their radius from the `connection_size` of their `SpawnNode`.
+    ///
+    /// Units are meters
+    pub radius: f32,
 }
-impl Stick {
-    pub fn new(point1: Entity, point2: Entity, length: f32) -> Self {
+impl<P> Stick<P> {
+    pub fn new(point1: P, point2: P, length: f32) -> Self {
         Self {
             point1,
             point2,
             length,
+            compliance: 0.,
+            kind: StickKind::Rigid,
+            max_strain: None,
+            radius: 0.,
+        }
+    }
+    /// Sets the compliance of the stick.
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+    /// Sets which directions the stick resists.
+    pub fn with_kind(mut self, kind: StickKind) -> Self {
+        self.kind = kind;
+        self
+    }
+    /// Sets the strain at which the stick breaks.
+    pub fn with_max_strain(mut self, max_strain: Option<f32>) -> Self {
+        self.max_strain = max_strain;
+        self
+    }
+    /// Sets the radius of the stick's collision capsule.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+    /// Calculates how far the stick is stretched relative to its length, given the
+    /// positions of its endpoints.
+    pub fn strain(&self, position1: Vec3, position2: Vec3) -> f32 {
+        (position1.distance(position2) - self.length) / self.length.max(f32::EPSILON)
+    }
+    /// Copies the stick's properties onto a new pair of endpoints.
+    pub fn with_points<Q>(&self, point1: Q, point2: Q) -> Stick<Q> {
+        Stick {
+            point1,
+            point2,
+            length: self.length,
+            compliance: self.compliance,
+            kind: self.kind,
+            max_strain: self.max_strain,
+            radius: self.radius,
+        }
+    }
+    /// The length the stick should be pulled or pushed to, or `None` if the stick is
+    /// slack at the current length.
+    pub fn target_length(&self, current_length: f32) -> Option<f32> {
+        match self.kind {
+            StickKind::Rigid => Some(self.length),
+            StickKind::Min => (current_length < self.length).then_some(self.length),
+            StickKind::Max => (current_length > self.length).then_some(self.length),
+            StickKind::Range { min, max } => {
+                if current_length < min {
+                    Some(min)
+                } else if current_length > max {
+                    Some(max)
+                } else {
+                    None
+                }
+            }
         }
     }
 }
+
+/// A motor attached to a stick that drives the stick's length over time, for walking
+/// creatures and pumping mechanisms.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Muscle {
+    /// How the length of the stick is animated.
+    pub drive: MuscleDrive,
+    /// The largest force the muscle can pull or push with. Under a heavier load the muscle
+    /// stalls and its stick gives way instead of reaching the driven length.
+    /// Unlimited when not specified.
+    ///
+    /// Units are N
+    pub max_force: Option<f32>,
+    /// Muscles sharing a channel can have their target length set together from Leptos.
+    pub channel: Option<u32>,
+    /// The simulation time the muscle was spawned at, which its drive is timed from.
+    /// Set when the muscle is spawned into the simulation.
+    ///
+    /// Units are seconds
+    pub spawn_time: f64,
+}
+impl Muscle {
+    pub fn new(drive: MuscleDrive) -> Self {
+        Self {
+            drive,
+            max_force: None,
+            channel: None,
+            spawn_time: 0.,
+        }
+    }
+    /// Sets the largest force the muscle can apply.
+    pub fn with_max_force(mut self, max_force: Option<f32>) -> Self {
+        self.max_force = max_force;
+        self
+    }
+    /// Sets the channel the muscle's target can be set on.
+    pub fn with_channel(mut self, channel: Option<u32>) -> Self {
+        self.channel = channel;
+        self
+    }
+    /// The length the muscle drives its stick to `time` seconds into the simulation,
+    /// or `None` if the drive has nothing to say.
+    pub fn length_at(&self, time: f64) -> Option<f32> {
+        // Wrap the time in double precision so long sessions don't make the drive stutter
+        let time = time - self.spawn_time;
+
+        match &self.drive {
+            MuscleDrive::Sine {
+                rest_length,
+                amplitude,
+                frequency,
+                phase,
+            } => {
+                let cycle = (std::f64::consts::TAU * *frequency as f64 * time)
+                    .rem_euclid(std::f64::consts::TAU) as f32;
+                Some(rest_length + amplitude * (cycle + phase).sin())
+            }
+            MuscleDrive::Keyframes { keyframes, looping } => {
+                let (&(first_time, first_length), &(last_time, last_length)) =
+                    (keyframes.first()?, keyframes.last()?);
+
+                let duration = (last_time - first_time) as f64;
+                let since_first = time - first_time as f64;
+                let time = match looping {
+                    PathLooping::Loop if duration > 0. => {
+                        first_time + since_first.rem_euclid(duration) as f32
+                    }
+                    PathLooping::PingPong if duration > 0. => {
+                        // Every other run heads back to the first keyframe
+                        let swing = since_first.rem_euclid(2. * duration);
+                        first_time + swing.min(2. * duration - swing) as f32
+                    }
+                    _ => time as f32,
+                };
+
+                if time <= first_time {
+                    return Some(first_length);
+                }
+                if time >= last_time {
+                    return Some(last_length);
+                }
+
+                // Interpolate between the keyframes either side of the time
+                keyframes.windows(2).find_map(|pair| {
+                    let [(start_time, start_length), (end_time, end_length)] = [pair[0], pair[1]];
+                    (time >= start_time && time <= end_time).then(|| {
+                        let span = (end_time - start_time).max(f32::EPSILON);
+                        start_length + (end_length - start_length) * (time - start_time) / span
+                    })
+                })
+            }
+            MuscleDrive::Target(length) => Some(*length),
+        }
+    }
+}
+
+/// How a `Muscle` animates the length of its stick.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MuscleDrive {
+    /// Oscillates the length around `rest_length`.
+    ///
+    /// Units are m for the lengths, Hz for the frequency and radians for the phase
+    Sine {
+        rest_length: f32,
+        amplitude: f32,
+        frequency: f32,
+        phase: f32,
+    },
+    /// Blends linearly between (time, length) keyframes sorted by time, holding the first and
+    /// last lengths outside of the track when it plays once. The times count from the muscle's spawn.
+    ///
+    /// Units are seconds and m
+    Keyframes {
+        keyframes: Vec<(f32, f32)>,
+        looping: PathLooping,
+    },
+    /// Holds the length last set from Leptos.
+    ///
+    /// Units are m
+    Target(f32),
+}
+
+/// An angle constraint across three points that resists the chain folding at `middle`,
+/// letting ropes, wires and fabric resist bending without extra helper sticks.
+///
+/// Inside the ECS the points are entities, inside a `VerletWorld` they are
+/// the indices of the points.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Bend<P = Entity> {
+    pub start: P,
+    /// The point the angle is measured at.
+    pub middle: P,
+    pub end: P,
+    /// The rest angle between `start` and `end` measured at `middle`.
+    /// A value of PI keeps the three points in a straight line.
+    ///
+    /// Units are radians
+    pub angle: f32,
+    /// How much the bend gives under load, the inverse of its stiffness.
+    /// A value of 0 is a rigid joint, larger values bend more easily.
+    ///
+    /// Units are rad/(N·m)
+    pub compliance: f32,
+}
+impl<P> Bend<P> {
+    pub fn new(start: P, middle: P, end: P, angle: f32) -> Self {
+        Self {
+            start,
+            middle,
+            end,
+            angle,
+            compliance: 0.,
+        }
+    }
+    /// Sets the compliance of the bend.
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+    /// Copies the bend's properties onto a new set of points.
+    pub fn with_points<Q>(&self, start: Q, middle: Q, end: Q) -> Bend<Q> {
+        Bend {
+            start,
+            middle,
+            end,
+            angle: self.angle,
+            compliance: self.compliance,
+        }
+    }
+    /// Calculates the angle between `start` and `end` measured at `middle`.
+    pub fn angle_between(start: Vec3, middle: Vec3, end: Vec3) -> f32 {
+        (start - middle).angle_between(end - middle)
+    }
+}
+
+/// A springy connection between two points that pushes and pulls them with a force instead of
+/// holding them at a fixed distance, letting suspensions, trampolines and jelly oscillate.
+///
+/// Springs are integrated explicitly, so very stiff springs on very light points need a higher
+/// tick rate or more substeps to stay stable.
+///
+/// Inside the ECS the endpoints are the point entities, inside a `VerletWorld` they are
+/// the indices of the points.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Spring<P = Entity> {
+    pub point1: P,
+    pub point2: P,
+    /// The distance the spring pushes and pulls the points towards.
+    ///
+    /// Units are m
+    pub rest_length: f32,
+    /// The Hooke's law constant of the spring.
+    ///
+    /// Units are N/m
+    pub stiffness: f32,
+    /// How strongly the spring resists the points moving towards or away from each other.
+    ///
+    /// Units are N·s/m
+    pub damping: f32,
+}
+impl<P> Spring<P> {
+    pub fn new(point1: P, point2: P, rest_length: f32, stiffness: f32, damping: f32) -> Self {
+        Self {
+            point1,
+            point2,
+            rest_length,
+            stiffness,
+            damping,
+        }
+    }
+    /// Copies the spring's properties onto a new pair of points.
+    pub fn with_points<Q>(&self, point1: Q, point2: Q) -> Spring<Q> {
+        Spring {
+            point1,
+            point2,
+            rest_length: self.rest_length,
+            stiffness: self.stiffness,
+            damping: self.damping,
+        }
+    }
+    /// The force the spring applies to its first point, from the positions and velocities (m/s)
+    /// of its endpoints. The second point feels the opposite force.
+    pub fn force(
+        &self,
+        position1: Vec3,
+        position2: Vec3,
+        velocity1: Vec3,
+        velocity2: Vec3,
+    ) -> Vec3 {
+        let delta = position2 - position1;
+        let Some(direction) = delta.try_normalize() else {
+            return Vec3::ZERO;
+        };
+
+        let stretch = delta.length() - self.rest_length;
+        let stretch_speed = (velocity2 - velocity1).dot(direction);
+
+        direction * (self.stiffness * stretch + self.damping * stretch_speed)
+    }
+}
+
+/// A triangle of surface between three points that catches the air, giving sheets like cloth
+/// drag and lift that depend on which way they face.
+///
+/// Inside the ECS the points are entities, inside a `VerletWorld` they are
+/// the indices of the points.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Panel<P = Entity> {
+    pub a: P,
+    pub b: P,
+    pub c: P,
+    /// How strongly the panel resists air flowing through it.
+    pub drag_coefficient: f32,
+    /// How strongly the panel is pushed sideways by air flowing across it at an angle.
+    pub lift_coefficient: f32,
+}
+impl<P> Panel<P> {
+    pub fn new(a: P, b: P, c: P) -> Self {
+        Self {
+            a,
+            b,
+            c,
+            drag_coefficient: 1.,
+            lift_coefficient: 0.5,
+        }
+    }
+    /// Sets the drag and lift coefficients of the panel.
+    pub fn with_coefficients(mut self, drag_coefficient: f32, lift_coefficient: f32) -> Self {
+        self.drag_coefficient = drag_coefficient;
+        self.lift_coefficient = lift_coefficient;
+        self
+    }
+    /// Copies the panel's properties onto a new set of points.
+    pub fn with_points<Q>(&self, a: Q, b: Q, c: Q) -> Panel<Q> {
+        Panel {
+            a,
+            b,
+            c,
+            drag_coefficient: self.drag_coefficient,
+            lift_coefficient: self.lift_coefficient,
+        }
+    }
+    /// The force the air applies to a panel with corners at `positions`, moving at `velocity`
+    /// (m/s) through air moving at `air_velocity` (m/s).
+    pub fn aerodynamic_force(
+        &self,
+        positions: [Vec3; 3],
+        velocity: Vec3,
+        air_velocity: Vec3,
+        air_density: f32,
+    ) -> Vec3 {
+        let [a, b, c] = positions;
+        let cross = (b - a).cross(c - a);
+        let area = cross.length() * 0.5;
+        let (Some(mut normal), Some(flow)) = (
+            cross.try_normalize(),
+            (velocity - air_velocity).try_normalize(),
+        ) else {
+            return Vec3::ZERO;
+        };
+        let speed = (velocity - air_velocity).length();
+
+        // Face the normal into the flow so the panel is pushed back regardless of its winding
+        if normal.dot(flow) < 0. {
+            normal = -normal;
+        }
+        let cos = normal.dot(flow);
+        let sin = (1. - cos * cos).max(0.).sqrt();
+        let dynamic_pressure = 0.5 * air_density * speed * speed * area;
+
+        // Drag opposes the motion through the air in proportion to the area facing it
+        let drag = -flow * dynamic_pressure * self.drag_coefficient * cos;
+        // Lift pushes across the flow, strongest when the panel is at 45 degrees to it
+        let lift = -(normal - flow * cos).normalize_or_zero()
+            * dynamic_pressure
+            * self.lift_coefficient
+            * cos
+            * sin;
+
+        drag + lift
+    }
+}
+
+/// A closed surface whose points are pushed out or pulled in to hold the area or volume
+/// it encloses, letting balloons, blobs and squishy balls keep their shape without bracing.
+///
+/// Inside the ECS the points are entities, inside a `VerletWorld` they are
+/// the indices of the points.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Pressure<P = Entity> {
+    /// The points making up the surface.
+    pub points: Vec<P>,
+    pub surface: PressureSurface,
+    /// The signed area or volume the surface enclosed when it was created.
+    ///
+    /// Units are m^2 for loops and m^3 for hulls
+    pub rest_volume: f32,
+    /// How much of the rest volume the surface is pushed towards.
+    /// Values above 1 inflate the body, values below 1 let it sag.
+    pub pressure: f32,
+    /// How much the enclosed volume gives under load, the inverse of its stiffness.
+    /// A value of 0 keeps the volume exact.
+    ///
+    /// Units are m^2/N for loops and m/N for hulls
+    pub compliance: f32,
+}
+impl<P> Pressure<P> {
+    /// A flat closed loop through the points in order, lying in the plane facing `normal`.
+    pub fn outline(points: Vec<P>, normal: Vec3) -> Self {
+        Self::new(points, PressureSurface::Loop { normal })
+    }
+    /// A closed triangulated hull over the points.
+    pub fn hull(points: Vec<P>, faces: Vec<[usize; 3]>) -> Self {
+        Self::new(points, PressureSurface::Hull { faces })
+    }
+    fn new(points: Vec<P>, surface: PressureSurface) -> Self {
+        Self {
+            points,
+            surface,
+            rest_volume: 0.,
+            pressure: 1.,
+            compliance: 0.,
+        }
+    }
+    /// Sets how much of the rest volume the surface is pushed towards.
+    pub fn with_pressure(mut self, pressure: f32) -> Self {
+        self.pressure = pressure;
+        self
+    }
+    /// Sets the compliance of the enclosed volume.
+    pub fn with_compliance(mut self, compliance: f32) -> Self {
+        self.compliance = compliance;
+        self
+    }
+    /// Sets the rest volume to the volume enclosed by the surface with its points at `positions`.
+    pub fn with_rest_positions(mut self, positions: &[Vec3]) -> Self {
+        self.rest_volume = self.surface.volume(positions);
+        self
+    }
+    /// Copies the surface's properties onto a new set of points.
+    pub fn with_points<Q>(&self, points: Vec<Q>) -> Pressure<Q> {
+        Pressure {
+            points,
+            surface: self.surface.clone(),
+            rest_volume: self.rest_volume,
+            pressure: self.pressure,
+            compliance: self.compliance,
+        }
+    }
+}
+
+/// The shape of the surface a `Pressure` encloses, indexing into its points.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PressureSurface {
+    /// A flat closed loop through the points in order, enclosing an area.
+    Loop { normal: Vec3 },
+    /// Triangles over the points enclosing a volume.
+    /// Consistently wound triangles give a consistent sign to the volume.
+    Hull { faces: Vec<[usize; 3]> },
+}
+impl PressureSurface {
+    /// The signed area or volume enclosed by the surface with its points at `positions`.
+    pub fn volume(&self, positions: &[Vec3]) -> f32 {
+        match self {
+            PressureSurface::Loop { normal } => {
+                let normal = normal.normalize_or_zero();
+                let count = positions.len();
+                (0..count)
+                    .map(|i| normal.dot(positions[i].cross(positions[(i + 1) % count])))
+                    .sum::<f32>()
+                    * 0.5
+            }
+            PressureSurface::Hull { faces } => {
+                faces
+                    .iter()
+                    .filter_map(|&[a, b, c]| {
+                        Some(
+                            positions
+                                .get(a)?
+                                .dot(positions.get(b)?.cross(*positions.get(c)?)),
+                        )
+                    })
+                    .sum::<f32>()
+                    / 6.
+            }
+        }
+    }
+    /// How the enclosed volume changes as each point at `positions` moves.
+    pub fn gradients(&self, positions: &[Vec3]) -> Vec<Vec3> {
+        let mut gradients = vec![Vec3::ZERO; positions.len()];
+
+        match self {
+            PressureSurface::Loop { normal } => {
+                let normal = normal.normalize_or_zero();
+                let count = positions.len();
+                for (i, gradient) in gradients.iter_mut().enumerate() {
+                    let next = positions[(i + 1) % count];
+                    let previous = positions[(i + count - 1) % count];
+                    *gradient = (next - previous).cross(normal) * 0.5;
+                }
+            }
+            PressureSurface::Hull { faces } => {
+                for &[a, b, c] in faces {
+                    let (Some(&pa), Some(&pb), Some(&pc)) =
+                        (positions.get(a), positions.get(b), positions.get(c))
+                    else {
+                        continue;
+                    };
+                    gradients[a] += pb.cross(pc) / 6.;
+                    gradients[b] += pc.cross(pa) / 6.;
+                    gradients[c] += pa.cross(pb) / 6.;
+                }
+            }
+        }
+
+        gradients
+    }
+}
+
+/// A group of points pulled towards the best-fit rigid placement of their rest shape,
+/// making crates and wheels rigid without cross-bracing them with sticks.
+///
+/// Inside the ECS the points are entities, inside a `VerletWorld` they are
+/// the indices of the points.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct ShapeMatch<P = Entity> {
+    /// The points making up the body.
+    pub points: Vec<P>,
+    /// Where each point sits relative to the body's center of mass at rest.
+    ///
+    /// Units are m
+    pub rest_offsets: Vec<Vec3>,
+    /// How far the points are pulled towards their matched positions each substep.
+    /// A value of 1 is fully rigid, smaller values give softer, wobblier bodies.
+    pub stiffness: f32,
+    /// The rotation of the body from its rest shape the last time it was matched.
+    pub rotation: Quat,
+}
+impl<P> ShapeMatch<P> {
+    pub fn new(points: Vec<P>, stiffness: f32) -> Self {
+        Self {
+            points,
+            rest_offsets: Vec::new(),
+            stiffness,
+            rotation: Quat::IDENTITY,
+        }
+    }
+    /// Sets the rest shape to the points at `positions` with the given `masses` (kg).
+    pub fn with_rest_shape(mut self, positions: &[Vec3], masses: &[f32]) -> Self {
+        let center = Self::center_of_mass(positions, masses);
+        self.rest_offsets = positions
+            .iter()
+            .map(|position| *position - center)
+            .collect();
+        self.rotation = Quat::IDENTITY;
+        self
+    }
+    /// Copies the body's properties onto a new set of points.
+    pub fn with_points<Q>(&self, points: Vec<Q>) -> ShapeMatch<Q> {
+        ShapeMatch {
+            points,
+            rest_offsets: self.rest_offsets.clone(),
+            stiffness: self.stiffness,
+            rotation: self.rotation,
+        }
+    }
+    /// The mass weighted center of the points at `positions`.
+    pub fn center_of_mass(positions: &[Vec3], masses: &[f32]) -> Vec3 {
+        let total_mass: f32 = masses.iter().sum();
+        if total_mass <= 0. {
+            return positions.iter().sum::<Vec3>() / positions.len().max(1) as f32;
+        }
+
+        positions
+            .iter()
+            .zip(masses)
+            .map(|(position, mass)| *position * *mass)
+            .sum::<Vec3>()
+            / total_mass
+    }
+    /// Finds the rotation and center that best fit the rest shape onto the points at
+    /// `positions`, starting the search from the last matched `rotation`.
+    ///
+    /// Uses the iterative rotation extraction from "A Robust Method to Extract the
+    /// Rotational Part of Deformations" (Müller et al. 2016), which also copes with
+    /// flat and degenerate bodies.
+    pub fn best_fit(&self, positions: &[Vec3], masses: &[f32]) -> (Quat, Vec3) {
+        let center = Self::center_of_mass(positions, masses);
+
+        // The moment matrix between the current and rest shapes
+        let moment = positions.iter().zip(&self.rest_offsets).zip(masses).fold(
+            Mat3::ZERO,
+            |moment, ((position, rest_offset), mass)| {
+                moment
+                    + Mat3::from_cols(
+                        (*position - center) * (rest_offset.x * mass),
+                        (*position - center) * (rest_offset.y * mass),
+                        (*position - center) * (rest_offset.z * mass),
+                    )
+            },
+        );
+
+        let mut rotation = self.rotation;
+        for _ in 0..SHAPE_MATCH_ITERATIONS {
+            let fit = Mat3::from_quat(rotation);
+            let torque = fit.x_axis.cross(moment.x_axis)
+                + fit.y_axis.cross(moment.y_axis)
+                + fit.z_axis.cross(moment.z_axis);
+            let alignment = fit.x_axis.dot(moment.x_axis)
+                + fit.y_axis.dot(moment.y_axis)
+                + fit.z_axis.dot(moment.z_axis);
+            let omega = torque / (alignment.abs() + 1.0e-9);
+
+            let angle = omega.length();
+            if angle < 1.0e-9 {
+                break;
+            }
+            rotation = (Quat::from_axis_angle(omega / angle, angle) * rotation).normalize();
+        }
+
+        (rotation, center)
+    }
+}
+
+/// What a connection in a `SpawnNode` is built from.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ConnectionType {
+    /// A `Stick` holding the points at their spawned distance.
+    #[default]
+    Stick,
+    /// A `Spring` resting at the points' spawned distance.
+    Spring { stiffness: f32, damping: f32 },
+}
+
+/// The directions a stick resists being pushed or pulled in.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StickKind {
+    /// Keeps the points exactly the stick's length apart.
+    #[default]
+    Rigid,
+    /// Only keeps the points from getting closer than the stick's length, like a strut.
+    Min,
+    /// Only keeps the points from getting further apart than the stick's length, like a rope or leash.
+    /// The stick goes slack under compression.
+    Max,
+    /// Keeps the distance between the points between `min` and `max`.
+    Range { min: f32, max: f32 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn muscle_keyframes_are_timed_from_the_spawn() {
+        let mut muscle = Muscle::new(MuscleDrive::Keyframes {
+            keyframes: vec![(0., 0.1), (1., 0.3)],
+            looping: PathLooping::Once,
+        });
+        muscle.spawn_time = 100.;
+
+        assert_eq!(muscle.length_at(100.), Some(0.1));
+        assert!((muscle.length_at(100.5).unwrap() - 0.2).abs() < 1e-5);
+        assert_eq!(muscle.length_at(101.5), Some(0.3));
+    }
+
+    #[test]
+    fn muscle_keyframes_ping_pong_like_paths() {
+        let muscle = Muscle::new(MuscleDrive::Keyframes {
+            keyframes: vec![(0., 0.1), (1., 0.3)],
+            looping: PathLooping::PingPong,
+        });
+
+        assert_eq!(muscle.length_at(1.), Some(0.3));
+        assert!((muscle.length_at(1.5).unwrap() - 0.2).abs() < 1e-5);
+        assert_eq!(muscle.length_at(2.), Some(0.1));
+        assert!((muscle.length_at(2.25).unwrap() - 0.15).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,327 @@
+use bevy::{
+    math::{Quat, Vec3},
+    prelude::Component,
+};
+
+use crate::core::{parameters::Point, solver::collision::coulomb_friction};
+
+/// The shape of a static collider, described in the collider's own frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColliderShape {
+    Sphere {
+        radius: f32,
+    },
+    /// A box centered on the collider's position.
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// A cylinder capped with half spheres, running along the collider's local y axis.
+    Capsule {
+        half_length: f32,
+        radius: f32,
+    },
+    /// An infinite plane through the collider's position facing along its local y axis.
+    /// Everything behind the plane is solid.
+    Plane,
+}
+
+/// A static shape the points collide against.
+///
+/// Colliders never move in response to the points hitting them.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub position: Vec3,
+    pub rotation: Quat,
+    /// Percent of a point's speed into the collider that's kept as a bounce.
+    pub restitution: f32,
+    /// The coefficient of static friction against the collider's surface.
+    pub static_friction: f32,
+    /// The coefficient of kinetic friction against the collider's surface.
+    pub kinetic_friction: f32,
+}
+impl Default for Collider {
+    fn default() -> Self {
+        Self {
+            shape: ColliderShape::Plane,
+            position: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            restitution: 0.5,
+            static_friction: 0.5,
+            kinetic_friction: 0.3,
+        }
+    }
+}
+impl Collider {
+    pub fn sphere(center: Vec3, radius: f32) -> Self {
+        Self {
+            shape: ColliderShape::Sphere { radius },
+            position: center,
+            ..Self::default()
+        }
+    }
+
+    pub fn cuboid(center: Vec3, half_extents: Vec3, rotation: Quat) -> Self {
+        Self {
+            shape: ColliderShape::Cuboid { half_extents },
+            position: center,
+            rotation,
+            ..Self::default()
+        }
+    }
+
+    /// A capsule whose spine runs from `start` to `end`.
+    pub fn capsule(start: Vec3, end: Vec3, radius: f32) -> Self {
+        let spine = end - start;
+        Self {
+            shape: ColliderShape::Capsule {
+                half_length: spine.length() * 0.5,
+                radius,
+            },
+            position: (start + end) * 0.5,
+            rotation: Quat::from_rotation_arc(Vec3::Y, spine.try_normalize().unwrap_or(Vec3::Y)),
+            ..Self::default()
+        }
+    }
+
+    /// A plane through `point` whose solid side is opposite `normal`.
+    pub fn plane(point: Vec3, normal: Vec3) -> Self {
+        Self {
+            shape: ColliderShape::Plane,
+            position: point,
+            rotation: Quat::from_rotation_arc(Vec3::Y, normal.try_normalize().unwrap_or(Vec3::Y)),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, static_friction: f32, kinetic_friction: f32) -> Self {
+        self.static_friction = static_friction;
+        self.kinetic_friction = kinetic_friction;
+        self
+    }
+
+    /// Finds how far a sphere at `position` sinks into the collider.
+    ///
+    /// Returns the direction that pushes the sphere out of the collider and the distance
+    /// it has to be pushed, or `None` if they don't touch.
+    pub fn contact(&self, position: Vec3, radius: f32) -> Option<(Vec3, f32)> {
+        let local = self.rotation.inverse() * (position - self.position);
+
+        let (local_normal, penetration) = match self.shape {
+            ColliderShape::Sphere {
+                radius: collider_radius,
+            } => away_from(local, Vec3::ZERO, collider_radius + radius)?,
+            ColliderShape::Capsule {
+                half_length,
+                radius: collider_radius,
+            } => {
+                let spine = Vec3::new(0., local.y.clamp(-half_length, half_length), 0.);
+                away_from(local, spine, collider_radius + radius)?
+            }
+            ColliderShape::Cuboid { half_extents } => {
+                let closest = local.clamp(-half_extents, half_extents);
+                if closest != local {
+                    away_from(local, closest, radius)?
+                } else {
+                    // Inside the box, leave through the nearest face
+                    let depths = half_extents - local.abs();
+                    let axis = if depths.x <= depths.y && depths.x <= depths.z {
+                        Vec3::X
+                    } else if depths.y <= depths.z {
+                        Vec3::Y
+                    } else {
+                        Vec3::Z
+                    };
+                    let sign = if local.dot(axis) < 0. { -1. } else { 1. };
+                    (axis * sign, depths.dot(axis) + radius)
+                }
+            }
+            ColliderShape::Plane => {
+                let penetration = radius - local.y;
+                if penetration <= 0. {
+                    return None;
+                }
+                (Vec3::Y, penetration)
+            }
+        };
+
+        Some((self.rotation * local_normal, penetration))
+    }
+
+    /// Finds where a sphere moving from `start` to `end` first touches the collider.
+    ///
+    /// Returns the fraction of the way to `end` the sphere gets before touching and the
+    /// direction out of the collider at that spot, or `None` if it never touches.
+    /// A sphere already touching the collider at `start` is left to `contact`.
+    pub fn sweep(&self, start: Vec3, end: Vec3, radius: f32) -> Option<(f32, Vec3)> {
+        if self.contact(start, radius).is_some() {
+            return None;
+        }
+
+        let inverse = self.rotation.inverse();
+        let start = inverse * (start - self.position);
+        let end = inverse * (end - self.position);
+
+        let (time, local_normal) = match self.shape {
+            ColliderShape::Sphere {
+                radius: collider_radius,
+            } => sweep_sphere(start, end, Vec3::ZERO, collider_radius + radius)?,
+            ColliderShape::Capsule {
+                half_length,
+                radius: collider_radius,
+            } => sweep_capsule(start, end, half_length, collider_radius + radius)?,
+            ColliderShape::Cuboid { half_extents } => {
+                sweep_box(start, end, half_extents + Vec3::splat(radius))?
+            }
+            ColliderShape::Plane => {
+                if end.y >= radius {
+                    return None;
+                }
+                ((start.y - radius) / (start.y - end.y), Vec3::Y)
+            }
+        };
+
+        Some((time, self.rotation * local_normal))
+    }
+
+    /// Pushes a point out of the collider, bounces any speed it had into it and applies
+    /// friction to any speed it had along it.
+    ///
+    /// Returns the distance the point was moved.
+    pub fn resolve(&self, point: &mut Point) -> f32 {
+        let Some((normal, penetration)) = self.contact(point.position, point.radius) else {
+            return 0.;
+        };
+
+        let velocity = coulomb_friction(
+            point.calculate_velocity(),
+            normal,
+            penetration,
+            self.static_friction,
+            self.kinetic_friction,
+        );
+
+        point.position += normal * penetration;
+
+        // Only points moving into the collider bounce
+        let normal_speed = velocity.dot(normal);
+        let new_velocity = if normal_speed < 0. {
+            velocity - normal * normal_speed * (1. + self.restitution)
+        } else {
+            velocity
+        };
+        point.prev_position = point.position - new_velocity;
+
+        penetration
+    }
+}
+
+/// The outward direction and depth of a sphere at `position` overlapping a sphere of
+/// `reach` around `center`.
+fn away_from(position: Vec3, center: Vec3, reach: f32) -> Option<(Vec3, f32)> {
+    let delta = position - center;
+    let distance = delta.length();
+    let penetration = reach - distance;
+    if penetration <= 0. {
+        return None;
+    }
+
+    // A point exactly at the center is pushed straight up
+    let normal = if distance > f32::EPSILON {
+        delta / distance
+    } else {
+        Vec3::Y
+    };
+
+    Some((normal, penetration))
+}
+
+/// Where a sphere moving from `start` to `end` first comes within `reach` of `center`.
+fn sweep_sphere(start: Vec3, end: Vec3, center: Vec3, reach: f32) -> Option<(f32, Vec3)> {
+    let offset = start - center;
+    let motion = end - start;
+
+    // Solve |offset + motion * t| = reach for the entering t
+    let a = motion.length_squared();
+    let b = offset.dot(motion);
+    let c = offset.length_squared() - reach * reach;
+    let discriminant = b * b - a * c;
+    if a <= f32::EPSILON || b >= 0. || discriminant < 0. {
+        return None;
+    }
+
+    let time = (-b - discriminant.sqrt()) / a;
+    if !(0. ..=1.).contains(&time) {
+        return None;
+    }
+
+    Some((time, (offset + motion * time) / reach))
+}
+
+/// Where a sphere moving from `start` to `end` first comes within `reach` of the spine running
+/// along the y axis from `-half_length` to `half_length`.
+fn sweep_capsule(start: Vec3, end: Vec3, half_length: f32, reach: f32) -> Option<(f32, Vec3)> {
+    let motion = end - start;
+
+    // The side of the capsule is a cylinder around the y axis
+    let a = motion.x * motion.x + motion.z * motion.z;
+    let b = start.x * motion.x + start.z * motion.z;
+    let c = start.x * start.x + start.z * start.z - reach * reach;
+    let discriminant = b * b - a * c;
+    if a > f32::EPSILON && b < 0. && discriminant >= 0. {
+        let time = (-b - discriminant.sqrt()) / a;
+        let hit = start + motion * time;
+        if (0. ..=1.).contains(&time) && hit.y.abs() <= half_length {
+            return Some((time, Vec3::new(hit.x, 0., hit.z) / reach));
+        }
+    }
+
+    // Otherwise it can only come in through one of the caps
+    let top = sweep_sphere(start, end, Vec3::Y * half_length, reach);
+    let bottom = sweep_sphere(start, end, Vec3::NEG_Y * half_length, reach);
+    match (top, bottom) {
+        (Some(top), Some(bottom)) => Some(if top.0 <= bottom.0 { top } else { bottom }),
+        (hit, None) | (None, hit) => hit,
+    }
+}
+
+/// Where a point moving from `start` to `end` first enters the box centered on the origin.
+fn sweep_box(start: Vec3, end: Vec3, half_extents: Vec3) -> Option<(f32, Vec3)> {
+    let motion = end - start;
+    let mut enter: f32 = 0.;
+    let mut exit: f32 = 1.;
+    let mut normal = None;
+
+    // Clip the motion against each pair of faces
+    for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+        let from = start.dot(axis);
+        let along = motion.dot(axis);
+        let extent = half_extents.dot(axis);
+
+        if along.abs() <= f32::EPSILON {
+            if from.abs() > extent {
+                return None;
+            }
+            continue;
+        }
+
+        let near = (-extent.copysign(along) - from) / along;
+        let far = (extent.copysign(along) - from) / along;
+        if near > enter {
+            enter = near;
+            normal = Some(-axis * along.signum());
+        }
+        exit = exit.min(far);
+        if enter > exit {
+            return None;
+        }
+    }
+
+    // No normal means the motion started inside the box
+    normal.map(|normal| (enter, normal))
+}
@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+
+use bevy::math::Vec3;
+
+use crate::core::{
+    parameters::{Point, Stick},
+    solver::spatial_hash::SpatialHash,
+};
+
+/// Two points whose spheres overlapped at the start of a substep.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointContact {
+    pub point1: usize,
+    pub point2: usize,
+    /// The speed the points were moving towards each other along the contact normal,
+    /// before the overlap was resolved.
+    ///
+    /// Units are m per substep
+    pub approach_speed: f32,
+}
+
+/// Applies Coulomb friction to the part of a velocity running along a contact surface.
+///
+/// `correction` is how far the contact pushed the point out along `normal`, which stands in for
+/// the normal force. A slide shorter than `static_friction` times the correction is stopped
+/// outright, longer slides are shortened by `kinetic_friction` times the correction.
+pub fn coulomb_friction(
+    velocity: Vec3,
+    normal: Vec3,
+    correction: f32,
+    static_friction: f32,
+    kinetic_friction: f32,
+) -> Vec3 {
+    let normal_velocity = normal * velocity.dot(normal);
+    let tangential_velocity = velocity - normal_velocity;
+    let slide = tangential_velocity.length();
+
+    if slide <= static_friction * correction {
+        return normal_velocity;
+    }
+
+    let kept = 1. - (kinetic_friction * correction / slide).min(1.);
+    normal_velocity + tangential_velocity * kept
+}
+
+/// Orders a pair of point indices so it can be compared regardless of direction.
+pub fn ordered_pair(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// Finds every pair of points whose spheres overlap, skipping any pair in `ignored`.
+///
+/// The points are bucketed into `hash` so each point is only compared against its neighbours.
+pub fn overlapping_points(
+    points: &[Point],
+    ignored: &HashSet<(usize, usize)>,
+    hash: &mut SpatialHash,
+) -> Vec<(usize, usize)> {
+    hash.clear();
+    // Only points with a size can collide
+    for (index, point) in points.iter().enumerate() {
+        if point.radius > 0. {
+            hash.insert_sphere(index, point.position, point.radius);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for (a, point_a) in points.iter().enumerate() {
+        if point_a.radius <= 0. {
+            continue;
+        }
+
+        for b in hash.query_sphere(point_a.position, point_a.radius) {
+            // Each pair is found from both sides, keep the one found from its lower index
+            if b <= a {
+                continue;
+            }
+
+            let point_b = &points[b];
+            let reach = point_a.radius + point_b.radius;
+            if point_a.position.distance_squared(point_b.position) < reach * reach
+                && !ignored.contains(&ordered_pair(a, b))
+            {
+                pairs.push((a, b));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Pushes each overlapping pair of points apart until their spheres only touch,
+/// weighted by their masses.
+///
+/// Returns the largest distance any point was moved.
+pub fn separate_points(points: &mut [Point], pairs: &[(usize, usize)]) -> f32 {
+    let mut max_delta: f32 = 0.0;
+
+    for &(a, b) in pairs {
+        let (point_a, point_b) = (points[a], points[b]);
+
+        let w_a = point_a.inverse_mass();
+        let w_b = point_b.inverse_mass();
+        let total_weight = w_a + w_b;
+        if total_weight <= 0. {
+            continue;
+        }
+
+        let delta = point_b.position - point_a.position;
+        let distance = delta.length();
+        let penetration = point_a.radius + point_b.radius - distance;
+        if penetration <= 0. || distance <= f32::EPSILON {
+            continue;
+        }
+        let normal = delta / distance;
+
+        let a_offset = -normal * (penetration * w_a / total_weight);
+        let b_offset = normal * (penetration * w_b / total_weight);
+
+        points[a].position += a_offset;
+        points[b].position += b_offset;
+
+        max_delta = max_delta.max(a_offset.length()).max(b_offset.length());
+    }
+
+    max_delta
+}
+
+/// Records how fast each overlapping pair of points is approaching so the bounce can be
+/// restored after the overlap has been resolved.
+pub fn point_contacts(points: &[Point], pairs: &[(usize, usize)]) -> Vec<PointContact> {
+    pairs
+        .iter()
+        .filter_map(|&(a, b)| {
+            let delta = points[b].position - points[a].position;
+            let normal = delta.try_normalize()?;
+
+            let relative_velocity = points[b].calculate_velocity() - points[a].calculate_velocity();
+
+            Some(PointContact {
+                point1: a,
+                point2: b,
+                approach_speed: -relative_velocity.dot(normal),
+            })
+        })
+        .collect()
+}
+
+/// Gives each contact a bounce of `restitution` times the speed it approached at.
+pub fn restitute_point_contacts(points: &mut [Point], contacts: &[PointContact], restitution: f32) {
+    for contact in contacts {
+        // Points that were already separating don't bounce
+        if contact.approach_speed <= 0. {
+            continue;
+        }
+
+        let (point_a, point_b) = (points[contact.point1], points[contact.point2]);
+
+        let w_a = point_a.inverse_mass();
+        let w_b = point_b.inverse_mass();
+        let total_weight = w_a + w_b;
+        if total_weight <= 0. {
+            continue;
+        }
+
+        let Some(normal) = (point_b.position - point_a.position).try_normalize() else {
+            continue;
+        };
+
+        // The separating speed the solve left the points with, and the one they should bounce with
+        let relative_velocity = point_b.calculate_velocity() - point_a.calculate_velocity();
+        let separating_speed = relative_velocity.dot(normal);
+        let bounce_speed = contact.approach_speed * restitution;
+        let impulse = bounce_speed - separating_speed;
+
+        // Velocity is implied by the previous position, so shift it to change the velocity
+        points[contact.point1].prev_position += normal * (impulse * w_a / total_weight);
+        points[contact.point2].prev_position -= normal * (impulse * w_b / total_weight);
+    }
+}
+
+/// The fraction along the segment from `start` to `end` closest to `position`.
+pub fn closest_on_segment(position: Vec3, start: Vec3, end: Vec3) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return 0.;
+    }
+
+    ((position - start).dot(segment) / length_squared).clamp(0., 1.)
+}
+
+/// The fractions along two segments at which they come closest to each other.
+pub fn closest_between_segments(start1: Vec3, end1: Vec3, start2: Vec3, end2: Vec3) -> (f32, f32) {
+    let d1 = end1 - start1;
+    let d2 = end2 - start2;
+    let r = start1 - start2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= f32::EPSILON && e <= f32::EPSILON {
+        return (0., 0.);
+    }
+    if a <= f32::EPSILON {
+        return (0., (f / e).clamp(0., 1.));
+    }
+
+    let c = d1.dot(r);
+    if e <= f32::EPSILON {
+        return ((-c / a).clamp(0., 1.), 0.);
+    }
+
+    let b = d1.dot(d2);
+    let denominator = a * e - b * b;
+    // Parallel segments can meet anywhere, start from the beginning of the first
+    let mut s = if denominator > f32::EPSILON {
+        ((b * f - c * e) / denominator).clamp(0., 1.)
+    } else {
+        0.
+    };
+    let mut t = (b * s + f) / e;
+
+    // Pull each fraction back onto its segment, adjusting the other to match
+    if t < 0. {
+        t = 0.;
+        s = (-c / a).clamp(0., 1.);
+    } else if t > 1. {
+        t = 1.;
+        s = ((b - c) / a).clamp(0., 1.);
+    }
+
+    (s, t)
+}
+
+/// Buckets every solid stick (one with a radius) into `hash` by the capsule it sweeps.
+pub fn hash_sticks(points: &[Point], sticks: &[Stick<usize>], hash: &mut SpatialHash) {
+    hash.clear();
+    for (index, stick) in sticks.iter().enumerate() {
+        if stick.radius <= 0. {
+            continue;
+        }
+        if let (Some(p1), Some(p2)) = (points.get(stick.point1), points.get(stick.point2)) {
+            hash.insert_segment(index, p1.position, p2.position, stick.radius);
+        }
+    }
+}
+
+/// Finds every (stick, point) pair where the point's sphere overlaps the stick's capsule.
+/// A stick never collides with its own endpoints.
+///
+/// The sticks have to be bucketed into `hash` with `hash_sticks` first.
+pub fn overlapping_stick_points(
+    points: &[Point],
+    sticks: &[Stick<usize>],
+    hash: &SpatialHash,
+) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+
+    for (point_index, point) in points.iter().enumerate() {
+        for stick_index in hash.query_sphere(point.position, point.radius) {
+            let stick = &sticks[stick_index];
+            if stick.point1 == point_index || stick.point2 == point_index {
+                continue;
+            }
+
+            let (start, end) = (points[stick.point1].position, points[stick.point2].position);
+            let closest = start.lerp(end, closest_on_segment(point.position, start, end));
+            let reach = stick.radius + point.radius;
+            if closest.distance_squared(point.position) < reach * reach {
+                pairs.push((stick_index, point_index));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Finds every pair of sticks whose capsules overlap.
+/// Sticks sharing an endpoint never collide with each other.
+///
+/// The sticks have to be bucketed into `hash` with `hash_sticks` first.
+pub fn overlapping_sticks(
+    points: &[Point],
+    sticks: &[Stick<usize>],
+    hash: &SpatialHash,
+) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+
+    for (a, stick_a) in sticks.iter().enumerate() {
+        if stick_a.radius <= 0. {
+            continue;
+        }
+        let (start_a, end_a) = (
+            points[stick_a.point1].position,
+            points[stick_a.point2].position,
+        );
+        let padding = Vec3::splat(stick_a.radius);
+
+        for b in hash.query_aabb(start_a.min(end_a) - padding, start_a.max(end_a) + padding) {
+            // Each pair is found from both sides, keep the one found from its lower index
+            if b <= a {
+                continue;
+            }
+
+            let stick_b = &sticks[b];
+            if [stick_b.point1, stick_b.point2].contains(&stick_a.point1)
+                || [stick_b.point1, stick_b.point2].contains(&stick_a.point2)
+            {
+                continue;
+            }
+
+            let (start_b, end_b) = (
+                points[stick_b.point1].position,
+                points[stick_b.point2].position,
+            );
+            let (s, t) = closest_between_segments(start_a, end_a, start_b, end_b);
+            let reach = stick_a.radius + stick_b.radius;
+            if start_a
+                .lerp(end_a, s)
+                .distance_squared(start_b.lerp(end_b, t))
+                < reach * reach
+            {
+                pairs.push((a, b));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Pushes each overlapping point out of the stick it's sunk into, sharing the correction
+/// between the point and the stick's endpoints by their masses and how close the contact
+/// is to each endpoint.
+///
+/// Returns the largest distance any point was moved.
+pub fn separate_stick_points(
+    points: &mut [Point],
+    sticks: &[Stick<usize>],
+    pairs: &[(usize, usize)],
+) -> f32 {
+    let mut max_delta: f32 = 0.0;
+
+    for &(stick_index, point_index) in pairs {
+        let stick = &sticks[stick_index];
+        let (start, end, point) = (
+            points[stick.point1],
+            points[stick.point2],
+            points[point_index],
+        );
+
+        let t = closest_on_segment(point.position, start.position, end.position);
+        let closest = start.position.lerp(end.position, t);
+        let delta = point.position - closest;
+        let distance = delta.length();
+        let penetration = stick.radius + point.radius - distance;
+        if penetration <= 0. || distance <= f32::EPSILON {
+            continue;
+        }
+        let normal = delta / distance;
+
+        let w_point = point.inverse_mass();
+        let w_start = start.inverse_mass() * (1. - t);
+        let w_end = end.inverse_mass() * t;
+        let total_weight = w_point + w_start * (1. - t) + w_end * t;
+        if total_weight <= 0. {
+            continue;
+        }
+        let lambda = penetration / total_weight;
+
+        let point_offset = normal * (lambda * w_point);
+        let start_offset = -normal * (lambda * w_start);
+        let end_offset = -normal * (lambda * w_end);
+
+        points[point_index].position += point_offset;
+        points[stick.point1].position += start_offset;
+        points[stick.point2].position += end_offset;
+
+        max_delta = max_delta
+            .max(point_offset.length())
+            .max(start_offset.length())
+            .max(end_offset.length());
+    }
+
+    max_delta
+}
+
+/// Pushes each overlapping pair of sticks apart until their capsules only touch, sharing
+/// the correction between their endpoints by their masses and how close the contact is to each.
+///
+/// Returns the largest distance any point was moved.
+pub fn separate_sticks(
+    points: &mut [Point],
+    sticks: &[Stick<usize>],
+    pairs: &[(usize, usize)],
+) -> f32 {
+    let mut max_delta: f32 = 0.0;
+
+    for &(a, b) in pairs {
+        let (stick_a, stick_b) = (&sticks[a], &sticks[b]);
+        let ends = [
+            stick_a.point1,
+            stick_a.point2,
+            stick_b.point1,
+            stick_b.point2,
+        ];
+        let [start_a, end_a, start_b, end_b] = ends.map(|index| points[index]);
+
+        let (s, t) = closest_between_segments(
+            start_a.position,
+            end_a.position,
+            start_b.position,
+            end_b.position,
+        );
+        let delta =
+            start_b.position.lerp(end_b.position, t) - start_a.position.lerp(end_a.position, s);
+        let distance = delta.length();
+        let penetration = stick_a.radius + stick_b.radius - distance;
+        if penetration <= 0. || distance <= f32::EPSILON {
+            continue;
+        }
+        let normal = delta / distance;
+
+        // How much of the correction each endpoint takes, and which way it's pushed
+        let shares = [
+            (start_a.inverse_mass() * (1. - s), -normal),
+            (end_a.inverse_mass() * s, -normal),
+            (start_b.inverse_mass() * (1. - t), normal),
+            (end_b.inverse_mass() * t, normal),
+        ];
+        let fractions = [1. - s, s, 1. - t, t];
+        let total_weight: f32 = shares
+            .iter()
+            .zip(fractions)
+            .map(|((weight, _), fraction)| weight * fraction)
+            .sum();
+        if total_weight <= 0. {
+            continue;
+        }
+        let lambda = penetration / total_weight;
+
+        for (&index, (weight, direction)) in ends.iter().zip(shares) {
+            let offset = direction * (lambda * weight);
+            points[index].position += offset;
+            max_delta = max_delta.max(offset.length());
+        }
+    }
+
+    max_delta
+}
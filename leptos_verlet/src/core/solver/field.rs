@@ -0,0 +1,340 @@
+use bevy::{math::Vec3, prelude::Component};
+
+/// The region of space a field acts in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldRegion {
+    /// An axis aligned box.
+    Cuboid {
+        center: Vec3,
+        half_extents: Vec3,
+    },
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+}
+impl FieldRegion {
+    pub fn contains(&self, position: Vec3) -> bool {
+        match *self {
+            FieldRegion::Cuboid {
+                center,
+                half_extents,
+            } => (position - center).abs().cmple(half_extents).all(),
+            FieldRegion::Sphere { center, radius } => {
+                position.distance_squared(center) <= radius * radius
+            }
+        }
+    }
+}
+
+/// How a gravity zone combines with the gravity around it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GravityZoneMode {
+    /// Points inside the zone feel only the zone's gravity.
+    #[default]
+    Override,
+    /// The zone's gravity is added on top of the gravity around it.
+    Add,
+}
+
+/// A region of space with its own gravity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct GravityZone {
+    pub region: FieldRegion,
+    /// Units are m/s^2
+    pub gravity: Vec3,
+    pub mode: GravityZoneMode,
+}
+impl GravityZone {
+    pub fn new(region: FieldRegion, gravity: Vec3) -> Self {
+        Self {
+            region,
+            gravity,
+            mode: GravityZoneMode::Override,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: GravityZoneMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+/// The gravity felt at a position, starting from the simulation's `gravity`.
+///
+/// The last overriding zone containing the position replaces the simulation's gravity,
+/// every additive zone containing it is added on top.
+pub fn gravity_at(zones: &[GravityZone], gravity: Vec3, position: Vec3) -> Vec3 {
+    let mut base = gravity;
+    let mut added = Vec3::ZERO;
+
+    for zone in zones.iter().filter(|zone| zone.region.contains(position)) {
+        match zone.mode {
+            GravityZoneMode::Override => base = zone.gravity,
+            GravityZoneMode::Add => added += zone.gravity,
+        }
+    }
+
+    base + added
+}
+
+/// How a force field pushes the points inside it.
+///
+/// Every force is in N and, like `external_forces`, moves lighter points further.
+///
+/// The corners of a `Panel` catch `Wind` and `Gust` through the panel instead of being pushed
+/// directly, as air moving at the speed that would push a square metre facing it with the force.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForceKind {
+    /// A steady push in one direction.
+    Wind { force: Vec3 },
+    /// Wind whose strength swings around `force` by up to `variation` (0 to 1) of it,
+    /// `frequency` times a second.
+    Gust {
+        force: Vec3,
+        variation: f32,
+        frequency: f32,
+    },
+    /// A swirl around the line through the field's position along `axis`.
+    /// Positive strengths turn counter-clockwise looking down the axis.
+    Vortex { axis: Vec3, strength: f32 },
+    /// A pull towards the field's position, a negative strength pushes away instead.
+    Attractor { strength: f32 },
+    /// Moving air. Doesn't push points directly, only the `Panel`s it flows past.
+    /// The speed swings around `velocity` by up to `variation` (0 to 1) of it, `frequency` times a second.
+    Airflow {
+        /// Units are m/s
+        velocity: Vec3,
+        variation: f32,
+        frequency: f32,
+    },
+    /// Smoothly varying pushes in every direction.
+    Turbulence {
+        strength: f32,
+        /// The size of the swirls. Units are m
+        scale: f32,
+        /// How quickly the swirls change. Units are 1/s
+        speed: f32,
+    },
+}
+
+impl ForceKind {
+    /// Does the field move the air, reaching the corners of panels through the panels.
+    pub fn moves_air(&self) -> bool {
+        matches!(
+            self,
+            ForceKind::Wind { .. } | ForceKind::Gust { .. } | ForceKind::Airflow { .. }
+        )
+    }
+}
+
+/// The smallest distance an inverse square falloff is measured from, so a field centered
+/// right on a point neither vanishes nor blows up.
+///
+/// Units are m
+pub const MIN_FALLOFF_DISTANCE: f32 = 0.01;
+
+/// How a force field weakens with the distance from its position.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Falloff {
+    /// Full strength everywhere.
+    #[default]
+    None,
+    /// Full strength at the position, fading to nothing at `radius`.
+    Linear { radius: f32 },
+    /// Full strength up to `min_distance`, then weakening with the square of the distance.
+    /// A `min_distance` under `MIN_FALLOFF_DISTANCE` is raised to it.
+    InverseSquare { min_distance: f32 },
+}
+impl Falloff {
+    pub fn scale(&self, distance: f32) -> f32 {
+        match *self {
+            Falloff::None => 1.,
+            Falloff::Linear { radius } => (1. - distance / radius.max(f32::EPSILON)).max(0.),
+            Falloff::InverseSquare { min_distance } => {
+                let min_distance = min_distance.max(MIN_FALLOFF_DISTANCE);
+                let ratio = min_distance / distance.max(min_distance);
+                ratio * ratio
+            }
+        }
+    }
+}
+
+/// A force acting on every point around a position.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ForceField {
+    pub kind: ForceKind,
+    /// The center of vortices, attractors and the falloff.
+    pub position: Vec3,
+    pub falloff: Falloff,
+    /// The field only acts inside this region, or everywhere if not specified.
+    pub region: Option<FieldRegion>,
+}
+impl ForceField {
+    pub fn new(kind: ForceKind, position: Vec3) -> Self {
+        Self {
+            kind,
+            position,
+            falloff: Falloff::None,
+            region: None,
+        }
+    }
+
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_region(mut self, region: FieldRegion) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// The force the field applies at a position `time` seconds into the simulation.
+    pub fn force_at(&self, position: Vec3, time: f64) -> Vec3 {
+        if !self.reaches(position) {
+            return Vec3::ZERO;
+        }
+
+        let offset = position - self.position;
+        let force = match self.kind {
+            ForceKind::Wind { force } => force,
+            ForceKind::Gust {
+                force,
+                variation,
+                frequency,
+            } => force * (1. + variation * cycle(frequency, time).sin()),
+            ForceKind::Vortex { axis, strength } => {
+                let axis = axis.normalize_or_zero();
+                // Only the part of the offset across the axis sets the direction of the swirl
+                let radial = offset - axis * offset.dot(axis);
+                axis.cross(radial).normalize_or_zero() * strength
+            }
+            ForceKind::Attractor { strength } => -offset.normalize_or_zero() * strength,
+            ForceKind::Airflow { .. } => Vec3::ZERO,
+            ForceKind::Turbulence {
+                strength,
+                scale,
+                speed,
+            } => {
+                // Split the drift into whole cells and the rest so it never swamps the position
+                let drift = time * speed as f64;
+                let cells = drift.floor();
+                let sample =
+                    position / scale.max(f32::EPSILON) + Vec3::splat((drift - cells) as f32);
+                let cells = cells as i64 as i32;
+                Vec3::new(
+                    value_noise(sample, cells, 0),
+                    value_noise(sample, cells, 1),
+                    value_noise(sample, cells, 2),
+                ) * strength
+            }
+        };
+
+        force * self.falloff.scale(offset.length())
+    }
+
+    /// The velocity of the air the field moves at a position `time` seconds into the simulation,
+    /// in air of `air_density` (kg/m^3).
+    ///
+    /// Wind and gusts blow at the speed whose drag on a square metre facing them is their force.
+    pub fn air_velocity_at(&self, position: Vec3, time: f64, air_density: f32) -> Vec3 {
+        if !self.reaches(position) {
+            return Vec3::ZERO;
+        }
+
+        match self.kind {
+            ForceKind::Airflow {
+                velocity,
+                variation,
+                frequency,
+            } => {
+                let swing = 1. + variation * cycle(frequency, time).sin();
+                velocity * swing * self.falloff.scale(position.distance(self.position))
+            }
+            ForceKind::Wind { .. } | ForceKind::Gust { .. } => {
+                // Drag = 0.5 * density * speed^2 * area, solved for the speed
+                let force = self.force_at(position, time);
+                let speed = (2. * force.length() / air_density.max(f32::EPSILON)).sqrt();
+                force.normalize_or_zero() * speed
+            }
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// Is the position inside the field's region.
+    fn reaches(&self, position: Vec3) -> bool {
+        self.region.is_none_or(|region| region.contains(position))
+    }
+}
+
+/// The total force every field applies at a position.
+/// A point on a panel (`on_panel`) is left to catch the fields moving the air through its panels.
+pub fn field_force_at(fields: &[ForceField], position: Vec3, time: f64, on_panel: bool) -> Vec3 {
+    fields
+        .iter()
+        .filter(|field| !(on_panel && field.kind.moves_air()))
+        .map(|field| field.force_at(position, time))
+        .sum()
+}
+
+/// The total velocity every field moves the air at a position, in air of `air_density` (kg/m^3).
+pub fn air_velocity_at(fields: &[ForceField], position: Vec3, time: f64, air_density: f32) -> Vec3 {
+    fields
+        .iter()
+        .map(|field| field.air_velocity_at(position, time, air_density))
+        .sum()
+}
+
+/// The angle through a cycle repeating `frequency` times a second, `time` seconds in.
+/// Wrapped in double precision so long sessions keep a smooth cycle.
+fn cycle(frequency: f32, time: f64) -> f32 {
+    (std::f64::consts::TAU * frequency as f64 * time).rem_euclid(std::f64::consts::TAU) as f32
+}
+
+/// Smooth noise between -1 and 1, interpolated between random values at every whole coordinate.
+/// Every coordinate is shifted by `shift` whole cells.
+fn value_noise(position: Vec3, shift: i32, seed: u32) -> f32 {
+    let cell = position.floor();
+    let fraction = position - cell;
+    // Smooth the interpolation so the noise has no creases at the cell edges
+    let t = fraction * fraction * (Vec3::splat(3.) - 2. * fraction);
+
+    let corner = |x: f32, y: f32, z: f32| lattice_value(cell + Vec3::new(x, y, z), shift, seed);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let x00 = lerp(corner(0., 0., 0.), corner(1., 0., 0.), t.x);
+    let x10 = lerp(corner(0., 1., 0.), corner(1., 1., 0.), t.x);
+    let x01 = lerp(corner(0., 0., 1.), corner(1., 0., 1.), t.x);
+    let x11 = lerp(corner(0., 1., 1.), corner(1., 1., 1.), t.x);
+
+    lerp(lerp(x00, x10, t.y), lerp(x01, x11, t.y), t.z)
+}
+
+/// A repeatable random value between -1 and 1 for a whole coordinate.
+fn lattice_value(cell: Vec3, shift: i32, seed: u32) -> f32 {
+    let mut hash = seed.wrapping_mul(0x9E37_79B9);
+    for coordinate in [cell.x, cell.y, cell.z] {
+        let coordinate = (coordinate as i32).wrapping_add(shift);
+        hash ^= (coordinate as u32).wrapping_mul(0x85EB_CA6B);
+        hash = hash.rotate_left(13).wrapping_mul(0xC2B2_AE35);
+    }
+    hash ^= hash >> 16;
+
+    hash as f32 / u32::MAX as f32 * 2. - 1.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_square_without_a_min_distance_still_acts() {
+        let falloff = Falloff::InverseSquare { min_distance: 0. };
+
+        assert_eq!(falloff.scale(0.), 1.);
+        assert_eq!(falloff.scale(MIN_FALLOFF_DISTANCE), 1.);
+        let quartered = falloff.scale(MIN_FALLOFF_DISTANCE * 2.);
+        assert!((quartered - 0.25).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,60 @@
+/// Groups points into islands, the sets of points joined to each other through any chain
+/// of constraints, so whole bodies can be put to sleep and woken together.
+#[derive(Clone, Debug, Default)]
+pub struct Islands {
+    parents: Vec<usize>,
+}
+impl Islands {
+    /// Starts every one of `count` points on an island of its own.
+    pub fn new(count: usize) -> Self {
+        Self {
+            parents: (0..count).collect(),
+        }
+    }
+
+    /// Merges the islands of two points.
+    pub fn join(&mut self, a: usize, b: usize) {
+        if a >= self.parents.len() || b >= self.parents.len() {
+            return;
+        }
+
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a != root_b {
+            self.parents[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+
+    /// Merges the islands of every point in `points`.
+    pub fn join_all(&mut self, points: &[usize]) {
+        if let Some((&first, rest)) = points.split_first() {
+            for &point in rest {
+                self.join(first, point);
+            }
+        }
+    }
+
+    /// The island the point belongs to, identified by one of its points.
+    pub fn find(&mut self, point: usize) -> usize {
+        let mut root = point;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Point everything on the way straight at the root so later lookups are quick
+        let mut current = point;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// The island of every point, identified by one of its points.
+    pub fn roots(&mut self) -> Vec<usize> {
+        (0..self.parents.len())
+            .map(|point| self.find(point))
+            .collect()
+    }
+}
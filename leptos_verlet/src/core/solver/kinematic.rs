@@ -0,0 +1,195 @@
+use bevy::{
+    math::{
+        Vec3,
+        curve::{Curve, EaseFunction},
+    },
+    prelude::Component,
+};
+
+/// The shape of the path a kinematic point follows, traced as the progress runs from 0 to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathShape {
+    /// A straight line from `start` to `end`.
+    Line { start: Vec3, end: Vec3 },
+    /// A full turn around `center` in the plane facing `axis`, counter-clockwise looking down the axis.
+    Circle {
+        center: Vec3,
+        axis: Vec3,
+        radius: f32,
+        /// The angle the turn starts at. Units are radians
+        start_angle: f32,
+    },
+    /// A cubic Bezier curve from `start` to `end`.
+    Bezier {
+        start: Vec3,
+        control1: Vec3,
+        control2: Vec3,
+        end: Vec3,
+    },
+    /// Blends linearly between (progress, position) keyframes sorted by progress,
+    /// holding the first and last positions outside of the track.
+    Keyframes(Vec<(f32, Vec3)>),
+}
+impl PathShape {
+    /// The position along the path at `progress` (0 to 1), or `None` if the path is empty.
+    pub fn position_at(&self, progress: f32) -> Option<Vec3> {
+        match self {
+            PathShape::Line { start, end } => Some(start.lerp(*end, progress)),
+            PathShape::Circle {
+                center,
+                axis,
+                radius,
+                start_angle,
+            } => {
+                let (right, up) = axis.try_normalize()?.any_orthonormal_pair();
+                let angle = start_angle + std::f32::consts::TAU * progress;
+                Some(*center + (right * angle.cos() + up * angle.sin()) * *radius)
+            }
+            PathShape::Bezier {
+                start,
+                control1,
+                control2,
+                end,
+            } => {
+                let t = progress;
+                let u = 1. - t;
+                Some(
+                    *start * (u * u * u)
+                        + *control1 * (3. * u * u * t)
+                        + *control2 * (3. * u * t * t)
+                        + *end * (t * t * t),
+                )
+            }
+            PathShape::Keyframes(keyframes) => {
+                let (&(first_progress, first_position), &(last_progress, last_position)) =
+                    (keyframes.first()?, keyframes.last()?);
+
+                if progress <= first_progress {
+                    return Some(first_position);
+                }
+                if progress >= last_progress {
+                    return Some(last_position);
+                }
+
+                // Blend between the keyframes either side of the progress
+                keyframes.windows(2).find_map(|pair| {
+                    let [(start_progress, start), (end_progress, end)] = [pair[0], pair[1]];
+                    (progress >= start_progress && progress <= end_progress).then(|| {
+                        let span = (end_progress - start_progress).max(f32::EPSILON);
+                        start.lerp(end, (progress - start_progress) / span)
+                    })
+                })
+            }
+        }
+    }
+}
+
+/// What a kinematic point or muscle does once it reaches the end of its path or keyframes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PathLooping {
+    /// Stops at the end of the path.
+    Once,
+    /// Jumps back to the start of the path and runs it again.
+    #[default]
+    Loop,
+    /// Runs the path backwards to the start, then forwards again.
+    PingPong,
+}
+
+/// Animates a locked point along a path, moving it by its own velocity so the bodies attached
+/// to it are dragged along naturally. Unlocked points ignore their path.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct KinematicPath {
+    pub shape: PathShape,
+    /// How long a single run along the path takes.
+    ///
+    /// Units are seconds
+    pub duration: f32,
+    /// How the progress along each run of the path speeds up and slows down.
+    pub easing: EaseFunction,
+    pub looping: PathLooping,
+    /// How long after the path is spawned its first run starts.
+    ///
+    /// Units are seconds
+    pub start_time: f32,
+    /// The simulation time the path was spawned at, which its runs are timed from.
+    /// Set when the path is spawned into the simulation.
+    ///
+    /// Units are seconds
+    pub spawn_time: f64,
+}
+impl KinematicPath {
+    pub fn new(shape: PathShape, duration: f32) -> Self {
+        Self {
+            shape,
+            duration,
+            easing: EaseFunction::Linear,
+            looping: PathLooping::Loop,
+            start_time: 0.,
+            spawn_time: 0.,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: EaseFunction) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_looping(mut self, looping: PathLooping) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn with_start_time(mut self, start_time: f32) -> Self {
+        self.start_time = start_time;
+        self
+    }
+
+    /// The position the point should be at `time` seconds into the simulation.
+    pub fn position_at(&self, time: f64) -> Option<Vec3> {
+        // Count the runs in double precision so long sessions don't make the motion stutter
+        let runs = (time - self.spawn_time - self.start_time as f64).max(0.)
+            / self.duration.max(f32::EPSILON) as f64;
+
+        let progress = match self.looping {
+            PathLooping::Once => runs.min(1.) as f32,
+            PathLooping::Loop => runs.fract() as f32,
+            PathLooping::PingPong => {
+                // Every odd run heads back to the start
+                let progress = runs.fract() as f32;
+                if runs as u64 % 2 == 1 {
+                    1. - progress
+                } else {
+                    progress
+                }
+            }
+        };
+
+        self.shape.position_at(self.easing.sample_clamped(progress))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_timed_from_their_spawn() {
+        let mut path = KinematicPath::new(
+            PathShape::Line {
+                start: Vec3::ZERO,
+                end: Vec3::X,
+            },
+            2.,
+        )
+        .with_looping(PathLooping::Once)
+        .with_start_time(1.);
+        path.spawn_time = 10.;
+
+        // Held at the start until the delay after the spawn runs out
+        assert_eq!(path.position_at(10.), Some(Vec3::ZERO));
+        assert_eq!(path.position_at(11.), Some(Vec3::ZERO));
+        assert_eq!(path.position_at(12.), Some(Vec3::X * 0.5));
+        assert_eq!(path.position_at(13.), Some(Vec3::X));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod collider;
+pub mod collision;
+pub mod field;
+pub mod island;
+pub mod kinematic;
+pub mod spatial_hash;
+pub mod world;
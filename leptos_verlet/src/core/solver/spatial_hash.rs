@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec3, Vec3};
+
+/// The most cells a single item may cover before it's treated as oversized.
+/// Oversized items (a stick stretched across the whole scene) are returned by every query
+/// rather than being written into thousands of cells.
+const MAX_CELLS_PER_ITEM: i64 = 512;
+
+/// A uniform grid that buckets items by the cells their bounding boxes cover,
+/// so only the items near a position or ray need to be checked.
+///
+/// Items are identified by an index chosen by the caller (the index of a point or stick).
+#[derive(Clone, Debug)]
+pub struct SpatialHash {
+    cell_size: f32,
+    /// The occupied cells, only ever holding the cells of the items inserted since the last clear.
+    cells: HashMap<IVec3, Vec<usize>>,
+    /// The emptied buckets of cleared cells, kept to reuse their allocations.
+    spare: Vec<Vec<usize>>,
+    oversized: Vec<usize>,
+    /// The bounds of every item inserted since the last clear.
+    bounds: Option<(Vec3, Vec3)>,
+}
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+impl SpatialHash {
+    /// Creates an empty hash whose cells are `cell_size` meters wide.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            spare: Vec::new(),
+            oversized: Vec::new(),
+            bounds: None,
+        }
+    }
+
+    /// Removes every item and changes the cell size, keeping the allocations.
+    pub fn reset(&mut self, cell_size: f32) {
+        self.cell_size = cell_size.max(f32::EPSILON);
+        self.clear();
+    }
+
+    /// Removes every item, keeping the allocations.
+    ///
+    /// The cells themselves are dropped so the hash doesn't fill up with every cell
+    /// a point has ever passed through.
+    pub fn clear(&mut self) {
+        for (_, mut items) in self.cells.drain() {
+            items.clear();
+            self.spare.push(items);
+        }
+        self.oversized.clear();
+        self.bounds = None;
+    }
+
+    /// Inserts an item covering the box between `min` and `max`.
+    pub fn insert(&mut self, item: usize, min: Vec3, max: Vec3) {
+        if !min.is_finite() || !max.is_finite() {
+            return;
+        }
+
+        let min_cell = self.cell(min);
+        let max_cell = self.cell(max);
+        let span = (max_cell - min_cell + IVec3::ONE).as_i64vec3();
+        if span.x * span.y * span.z > MAX_CELLS_PER_ITEM {
+            self.oversized.push(item);
+            return;
+        }
+
+        self.bounds = Some(match self.bounds {
+            Some((bounds_min, bounds_max)) => (bounds_min.min(min), bounds_max.max(max)),
+            None => (min, max),
+        });
+
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                for z in min_cell.z..=max_cell.z {
+                    self.cells
+                        .entry(IVec3::new(x, y, z))
+                        .or_insert_with(|| self.spare.pop().unwrap_or_default())
+                        .push(item);
+                }
+            }
+        }
+    }
+
+    /// Inserts an item covering a sphere.
+    pub fn insert_sphere(&mut self, item: usize, center: Vec3, radius: f32) {
+        self.insert(
+            item,
+            center - Vec3::splat(radius),
+            center + Vec3::splat(radius),
+        );
+    }
+
+    /// Inserts an item covering the line between two positions, padded by `radius`.
+    pub fn insert_segment(&mut self, item: usize, start: Vec3, end: Vec3, radius: f32) {
+        self.insert(
+            item,
+            start.min(end) - Vec3::splat(radius),
+            start.max(end) + Vec3::splat(radius),
+        );
+    }
+
+    /// Returns every item whose cells overlap the box between `min` and `max`.
+    /// The result may contain items that are near, but outside, the box.
+    pub fn query_aabb(&self, min: Vec3, max: Vec3) -> Vec<usize> {
+        let mut items = self.oversized.clone();
+
+        let (min_cell, max_cell) = match self.clamp_to_bounds(min, max) {
+            Some(clamped) => clamped,
+            None => return items,
+        };
+
+        for x in min_cell.x..=max_cell.x {
+            for y in min_cell.y..=max_cell.y {
+                for z in min_cell.z..=max_cell.z {
+                    if let Some(cell_items) = self.cells.get(&IVec3::new(x, y, z)) {
+                        items.extend_from_slice(cell_items);
+                    }
+                }
+            }
+        }
+
+        items.sort_unstable();
+        items.dedup();
+        items
+    }
+
+    /// Returns every item whose cells overlap a sphere.
+    pub fn query_sphere(&self, center: Vec3, radius: f32) -> Vec<usize> {
+        self.query_aabb(center - Vec3::splat(radius), center + Vec3::splat(radius))
+    }
+
+    /// Returns every item whose cells come within `tolerance` of a ray.
+    /// The result may contain items that are near, but further than `tolerance` from, the ray.
+    pub fn query_ray(&self, origin: Vec3, direction: Vec3, tolerance: f32) -> Vec<usize> {
+        let mut items = self.oversized.clone();
+
+        let Some(direction) = direction.try_normalize() else {
+            return items;
+        };
+        let Some((bounds_min, bounds_max)) = self.bounds else {
+            return items;
+        };
+
+        // Only march the part of the ray that passes through the occupied space
+        let padding = Vec3::splat(tolerance);
+        let Some((enter, exit)) = ray_box_span(
+            origin,
+            direction,
+            bounds_min - padding,
+            bounds_max + padding,
+        ) else {
+            return items;
+        };
+
+        // Any item within tolerance of the ray is within this reach of the closest sample
+        let step = self.cell_size * 0.5;
+        let reach = Vec3::splat(tolerance + step * 0.5);
+
+        let mut distance = enter.max(0.);
+        loop {
+            let sample = origin + direction * distance.min(exit);
+            items.extend(self.query_aabb(sample - reach, sample + reach));
+
+            if distance >= exit {
+                break;
+            }
+            distance += step;
+        }
+
+        items.sort_unstable();
+        items.dedup();
+        items
+    }
+
+    fn cell(&self, position: Vec3) -> IVec3 {
+        (position / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Limits a box to the occupied cells, or `None` if they don't overlap.
+    fn clamp_to_bounds(&self, min: Vec3, max: Vec3) -> Option<(IVec3, IVec3)> {
+        let (bounds_min, bounds_max) = self.bounds?;
+        let min = min.max(bounds_min);
+        let max = max.min(bounds_max);
+        if min.cmpgt(max).any() {
+            return None;
+        }
+        Some((self.cell(min), self.cell(max)))
+    }
+}
+
+/// Finds the distances along a ray at which it enters and exits a box.
+fn ray_box_span(origin: Vec3, direction: Vec3, min: Vec3, max: Vec3) -> Option<(f32, f32)> {
+    let inverse = direction.recip();
+    let t1 = (min - origin) * inverse;
+    let t2 = (max - origin) * inverse;
+
+    let enter = t1.min(t2).max_element();
+    let exit = t1.max(t2).min_element();
+
+    (enter <= exit && exit >= 0.).then_some((enter, exit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_forgets_the_visited_cells() {
+        let mut hash = SpatialHash::new(0.1);
+
+        // A point falling through a thousand cells, rehashed every tick
+        for tick in 0..1000 {
+            hash.clear();
+            hash.insert_sphere(0, Vec3::new(0., -(tick as f32) * 0.1, 0.), 0.01);
+        }
+
+        assert!(hash.cells.len() <= 8);
+        assert_eq!(
+            hash.query_ray(Vec3::new(0., -99.9, 1.), Vec3::NEG_Z, 0.05),
+            vec![0]
+        );
+        assert!(hash.query_sphere(Vec3::ZERO, 0.05).is_empty());
+    }
+}
@@ -0,0 +1,2048 @@
+use std::collections::HashSet;
+
+use bevy::{math::Vec3, prelude::Resource};
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::core::{
+    container_bounds::{BoundaryMode, BoundsShape},
+    parameters::{
+        Bend, Muscle, Panel, Point, Pressure, PressureSurface, ShapeMatch, SimulationSettings,
+        Spring, Stick,
+    },
+    solver::{
+        collider::Collider,
+        collision::{
+            coulomb_friction, hash_sticks, ordered_pair, overlapping_points,
+            overlapping_stick_points, overlapping_sticks, point_contacts, restitute_point_contacts,
+            separate_points, separate_stick_points, separate_sticks,
+        },
+        field::{ForceField, GravityZone, air_velocity_at, field_force_at, gravity_at},
+        island::Islands,
+        kinematic::KinematicPath,
+        spatial_hash::SpatialHash,
+    },
+};
+
+/// The halvings used to find where a point crossed the outline of the bounds.
+const OUTLINE_SWEEP_ITERATIONS: u32 = 12;
+
+/// A headless verlet simulation.
+///
+/// The world owns plain copies of the points and sticks and steps them without touching
+/// the ECS, the renderer or the browser, so the exact same solver the Bevy plugins use can be
+/// ran natively (tests, servers, offline batch jobs).
+///
+/// Sticks, springs, bends, panels, pressures and shape matches reference points by their
+/// index in `points`.
+#[derive(Resource, Clone, Debug)]
+pub struct VerletWorld {
+    pub points: Vec<Point>,
+    pub sticks: Vec<Stick<usize>>,
+    pub springs: Vec<Spring<usize>>,
+    /// The muscles driving the sticks, each paired with the index of the stick it drives.
+    pub muscles: Vec<(usize, Muscle)>,
+    pub bends: Vec<Bend<usize>>,
+    pub panels: Vec<Panel<usize>>,
+    /// The closed surfaces holding their area or volume.
+    pub pressures: Vec<Pressure<usize>>,
+    /// The groups of points held to their rest shapes.
+    pub shape_matches: Vec<ShapeMatch<usize>>,
+    /// The paths animating locked points, each paired with the index of the point it drives.
+    pub kinematic_paths: Vec<(usize, KinematicPath)>,
+    /// The static shapes the points collide against.
+    pub colliders: Vec<Collider>,
+    /// The regions with their own gravity.
+    pub gravity_zones: Vec<GravityZone>,
+    /// The fields pushing the points around.
+    pub force_fields: Vec<ForceField>,
+    /// The time simulated since the world was created or last reset, used to animate the fields,
+    /// paths and muscles. Kept in double precision so long sessions animate as smoothly as short ones.
+    ///
+    /// Units are seconds
+    pub time: f64,
+    pub settings: SimulationSettings,
+    /// The sticks that broke during the last call to `step`.
+    pub broken_sticks: Vec<BrokenStick>,
+    /// The indices of the points that reached a `BoundaryMode::Despawn` side or left the
+    /// `kill_volume` during the last call to `step`, and why. They're left in `points` so every
+    /// index stays valid, it's up to the owner of the world to remove them.
+    pub despawned_points: Vec<(usize, DespawnReason)>,
+    /// The generator used to randomize the solve order, seeded from `settings.seed`.
+    rng: StdRng,
+    /// The accumulated XPBD multiplier of each stick over the current substep.
+    stick_lambdas: Vec<f32>,
+    /// The largest force each stick can apply this substep, limited by the muscle driving it.
+    stick_force_limits: Vec<f32>,
+    /// The accumulated XPBD multiplier of each bend over the current substep.
+    bend_lambdas: Vec<f32>,
+    /// The accumulated XPBD multiplier of each pressure over the current substep.
+    pressure_lambdas: Vec<f32>,
+    /// The broadphase used to find colliding points, kept to reuse its allocations.
+    point_hash: SpatialHash,
+    /// The broadphase used to find colliding sticks, kept to reuse its allocations.
+    stick_hash: SpatialHash,
+    /// The fields, zones and gravity the points felt last step, so every body can be woken
+    /// when they change.
+    last_forces: (Vec<ForceField>, Vec<GravityZone>, Vec3),
+}
+impl Default for VerletWorld {
+    fn default() -> Self {
+        Self::new(SimulationSettings::default())
+    }
+}
+impl VerletWorld {
+    pub fn new(settings: SimulationSettings) -> Self {
+        Self {
+            points: Vec::new(),
+            sticks: Vec::new(),
+            springs: Vec::new(),
+            muscles: Vec::new(),
+            kinematic_paths: Vec::new(),
+            bends: Vec::new(),
+            panels: Vec::new(),
+            pressures: Vec::new(),
+            shape_matches: Vec::new(),
+            colliders: Vec::new(),
+            gravity_zones: Vec::new(),
+            force_fields: Vec::new(),
+            time: 0.,
+            settings,
+            broken_sticks: Vec::new(),
+            despawned_points: Vec::new(),
+            rng: seeded_rng(settings.seed),
+            stick_lambdas: Vec::new(),
+            stick_force_limits: Vec::new(),
+            bend_lambdas: Vec::new(),
+            pressure_lambdas: Vec::new(),
+            point_hash: SpatialHash::new(settings.spatial_hash_cell_size),
+            stick_hash: SpatialHash::new(settings.spatial_hash_cell_size),
+            last_forces: (Vec::new(), Vec::new(), settings.gravity),
+        }
+    }
+
+    /// Restarts the random number generator from `settings.seed`.
+    /// With a fixed seed, stepping the same points and sticks again reproduces the same trajectories.
+    pub fn reseed(&mut self) {
+        self.rng = seeded_rng(self.settings.seed);
+    }
+
+    /// Adds a point to the world and returns its index.
+    pub fn add_point(&mut self, point: Point) -> usize {
+        self.points.push(point);
+        self.points.len() - 1
+    }
+
+    /// Animates the locked point at `point` along a path, replacing any path already driving it.
+    pub fn add_kinematic_path(&mut self, point: usize, path: KinematicPath) {
+        self.kinematic_paths.retain(|(driven, _)| *driven != point);
+        self.kinematic_paths.push((point, path));
+    }
+
+    /// Connects two points with a stick whose length is their current distance apart.
+    /// Returns the index of the stick.
+    pub fn add_stick(&mut self, point1: usize, point2: usize) -> usize {
+        let length = self.points[point1]
+            .position
+            .distance(self.points[point2].position);
+        self.sticks.push(Stick::new(point1, point2, length));
+        self.sticks.len() - 1
+    }
+
+    /// Connects two points with a spring resting at their current distance apart.
+    /// Returns the index of the spring.
+    pub fn add_spring(
+        &mut self,
+        point1: usize,
+        point2: usize,
+        stiffness: f32,
+        damping: f32,
+    ) -> usize {
+        let rest_length = self.points[point1]
+            .position
+            .distance(self.points[point2].position);
+        self.springs
+            .push(Spring::new(point1, point2, rest_length, stiffness, damping));
+        self.springs.len() - 1
+    }
+
+    /// Attaches a muscle to the stick at `stick`, replacing any muscle already driving it.
+    pub fn add_muscle(&mut self, stick: usize, muscle: Muscle) {
+        self.muscles.retain(|(driven, _)| *driven != stick);
+        self.muscles.push((stick, muscle));
+    }
+
+    /// Adds a bend at `middle` whose rest angle is the current angle between the points.
+    /// Returns the index of the bend.
+    pub fn add_bend(&mut self, start: usize, middle: usize, end: usize) -> usize {
+        let angle = Bend::<usize>::angle_between(
+            self.points[start].position,
+            self.points[middle].position,
+            self.points[end].position,
+        );
+        self.bends.push(Bend::new(start, middle, end, angle));
+        self.bends.len() - 1
+    }
+
+    /// Adds a panel between three points and returns its index.
+    pub fn add_panel(&mut self, a: usize, b: usize, c: usize) -> usize {
+        self.panels.push(Panel::new(a, b, c));
+        self.panels.len() - 1
+    }
+
+    /// Encloses the points in a pressurized surface resting at the area or volume
+    /// they currently enclose. Returns the index of the pressure.
+    pub fn add_pressure(&mut self, points: Vec<usize>, surface: PressureSurface) -> usize {
+        let positions: Vec<Vec3> = points
+            .iter()
+            .map(|&index| self.points[index].position)
+            .collect();
+        let pressure = match surface {
+            PressureSurface::Loop { normal } => Pressure::outline(points, normal),
+            PressureSurface::Hull { faces } => Pressure::hull(points, faces),
+        };
+        self.pressures
+            .push(pressure.with_rest_positions(&positions));
+        self.pressures.len() - 1
+    }
+
+    /// Holds the points to the shape they're currently in with the given `stiffness` (0 to 1).
+    /// Returns the index of the shape match.
+    pub fn add_shape_match(&mut self, points: Vec<usize>, stiffness: f32) -> usize {
+        let positions: Vec<Vec3> = points
+            .iter()
+            .map(|&index| self.points[index].position)
+            .collect();
+        let masses: Vec<f32> = points
+            .iter()
+            .map(|&index| self.points[index].mass)
+            .collect();
+        self.shape_matches
+            .push(ShapeMatch::new(points, stiffness).with_rest_shape(&positions, &masses));
+        self.shape_matches.len() - 1
+    }
+
+    /// Advances the world by a single physics tick (`settings.tick_delta()` seconds),
+    /// running every substep of the tick.
+    ///
+    /// Returns the largest distance any point moved during the tick.
+    pub fn step(&mut self) -> f32 {
+        let dt = self.settings.substep_delta();
+        let mut max_delta: f32 = 0.0;
+        self.broken_sticks.clear();
+        self.despawned_points.clear();
+
+        // The pairs of points joined by a stick or a spring
+        let connected: HashSet<(usize, usize)> = self
+            .sticks
+            .iter()
+            .map(|stick| ordered_pair(stick.point1, stick.point2))
+            .chain(
+                self.springs
+                    .iter()
+                    .map(|spring| ordered_pair(spring.point1, spring.point2)),
+            )
+            .collect();
+
+        let islands = self.wake_islands();
+        // Where the points started the tick, to tell how far they really moved over it
+        let tick_start: Vec<Vec3> = if self.settings.sleeping {
+            self.points.iter().map(|point| point.position).collect()
+        } else {
+            Vec::new()
+        };
+
+        for _ in 0..self.settings.substeps.max(1) {
+            // Every substep starts the compliant constraints from rest
+            self.stick_lambdas.clear();
+            self.stick_lambdas.resize(self.sticks.len(), 0.);
+            self.bend_lambdas.clear();
+            self.bend_lambdas.resize(self.bends.len(), 0.);
+            self.pressure_lambdas.clear();
+            self.pressure_lambdas.resize(self.pressures.len(), 0.);
+
+            self.actuate_sticks();
+
+            max_delta = max_delta.max(self.update_points(dt));
+
+            // Smooth the motion before the constraints, so nothing smooths points back out of them
+            self.filter();
+
+            // Catch the points that moved clean through something this substep
+            if self.settings.continuous_collision {
+                self.sweep_points();
+            }
+
+            // Remember how fast colliding points approached so they can bounce once they're separated
+            let contacts = if self.settings.point_collisions {
+                self.point_hash.reset(self.settings.spatial_hash_cell_size);
+                let pairs = overlapping_points(&self.points, &connected, &mut self.point_hash);
+                self.wake_contacts(&islands, &pairs);
+                point_contacts(&self.points, &pairs)
+            } else {
+                Vec::new()
+            };
+
+            // Sleeping bodies caught by a solid stick wake up too
+            if self.settings.sleeping && self.settings.stick_collisions {
+                self.stick_hash.reset(self.settings.spatial_hash_cell_size);
+                hash_sticks(&self.points, &self.sticks, &mut self.stick_hash);
+                let sticks = &self.sticks;
+                let pairs: Vec<(usize, usize)> =
+                    overlapping_stick_points(&self.points, sticks, &self.stick_hash)
+                        .into_iter()
+                        .map(|(stick, point)| (sticks[stick].point1, point))
+                        .chain(
+                            overlapping_sticks(&self.points, sticks, &self.stick_hash)
+                                .into_iter()
+                                .map(|(a, b)| (sticks[a].point1, sticks[b].point1)),
+                        )
+                        .collect();
+                self.wake_contacts(&islands, &pairs);
+            }
+
+            max_delta = max_delta.max(self.converge(dt, &connected));
+
+            restitute_point_contacts(&mut self.points, &contacts, self.settings.coeff_restitution);
+
+            self.time += dt as f64;
+        }
+
+        self.break_overstrained_sticks();
+
+        self.settle_islands(&islands, &tick_start);
+
+        self.despawn_escaped_points();
+        self.despawned_points.sort_by_key(|(index, _)| *index);
+        self.despawned_points.dedup_by_key(|(index, _)| *index);
+
+        max_delta
+    }
+
+    /// Finds the island every point belongs to, identified by one of its points, and wakes
+    /// every island with an awake point on it. Every island is woken when sleeping is turned
+    /// off or the fields, zones or gravity changed since the last step.
+    fn wake_islands(&mut self) -> Vec<usize> {
+        let roots = self.islands().roots();
+
+        let forces = (
+            self.force_fields.clone(),
+            self.gravity_zones.clone(),
+            self.settings.gravity,
+        );
+        let forces_changed = forces != self.last_forces;
+        self.last_forces = forces;
+
+        let mut awake = vec![!self.settings.sleeping || forces_changed; self.points.len()];
+        for root in self.driven_roots(&roots) {
+            awake[root] = true;
+        }
+        for (index, point) in self.points.iter().enumerate() {
+            if !point.asleep {
+                awake[roots[index]] = true;
+            }
+        }
+
+        for (index, point) in self.points.iter_mut().enumerate() {
+            if point.asleep && awake[roots[index]] {
+                point.wake();
+            }
+        }
+
+        roots
+    }
+
+    /// Groups the points into islands joined by sticks, springs, pressures and shape matches.
+    fn islands(&self) -> Islands {
+        let mut islands = Islands::new(self.points.len());
+        for stick in &self.sticks {
+            islands.join(stick.point1, stick.point2);
+        }
+        for spring in &self.springs {
+            islands.join(spring.point1, spring.point2);
+        }
+        for pressure in &self.pressures {
+            islands.join_all(&pressure.points);
+        }
+        for shape_match in &self.shape_matches {
+            islands.join_all(&shape_match.points);
+        }
+        islands
+    }
+
+    /// Wakes the islands of the given points, and of any point touching one of the
+    /// (position, radius) spheres, for when something they lean on or hang from was removed.
+    pub fn wake_islands_around(&mut self, points: &[usize], spheres: &[(Vec3, f32)]) {
+        if !self.settings.sleeping || (points.is_empty() && spheres.is_empty()) {
+            return;
+        }
+
+        let mut islands = self.islands();
+        let mut woken = HashSet::new();
+        for &point in points.iter().filter(|&&point| point < self.points.len()) {
+            woken.insert(islands.find(point));
+        }
+        for (index, point) in self.points.iter().enumerate() {
+            let touching = spheres
+                .iter()
+                .any(|&(center, radius)| point.position.distance(center) <= point.radius + radius);
+            if touching {
+                woken.insert(islands.find(index));
+            }
+        }
+
+        for (index, point) in self.points.iter_mut().enumerate() {
+            if point.asleep && woken.contains(&islands.find(index)) {
+                point.wake();
+            }
+        }
+    }
+
+    /// The islands holding a muscle or a kinematic path, which are never put to sleep.
+    fn driven_roots<'a>(&'a self, roots: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+        let muscle_roots = self
+            .muscles
+            .iter()
+            .filter_map(|(stick, _)| roots.get(self.sticks.get(*stick)?.point1).copied());
+        let path_roots = self
+            .kinematic_paths
+            .iter()
+            .filter_map(|(point, _)| roots.get(*point).copied());
+
+        muscle_roots.chain(path_roots)
+    }
+
+    /// Wakes the island of any sleeping point touching an awake one.
+    fn wake_contacts(&mut self, roots: &[usize], pairs: &[(usize, usize)]) {
+        let woken: HashSet<usize> = pairs
+            .iter()
+            .filter_map(
+                |&(a, b)| match (self.points[a].asleep, self.points[b].asleep) {
+                    (true, false) => Some(roots[a]),
+                    (false, true) => Some(roots[b]),
+                    _ => None,
+                },
+            )
+            .collect();
+        if woken.is_empty() {
+            return;
+        }
+
+        for (index, point) in self.points.iter_mut().enumerate() {
+            if point.asleep && woken.contains(&roots[index]) {
+                point.wake();
+            }
+        }
+    }
+
+    /// Puts every island to sleep whose points have all stayed under the `sleep_speed`
+    /// for the `sleep_delay`.
+    ///
+    /// The speed is measured over the whole tick from `tick_start`, as points resting against
+    /// the bounds keep being pushed back to the same spot with a velocity that never settles.
+    fn settle_islands(&mut self, roots: &[usize], tick_start: &[Vec3]) {
+        if !self.settings.sleeping {
+            return;
+        }
+        let tick_delta = self.settings.tick_delta();
+
+        let mut restless = vec![false; self.points.len()];
+        for (index, point) in self.points.iter_mut().enumerate() {
+            if point.asleep {
+                continue;
+            }
+
+            let speed = point.position.distance(tick_start[index]) / tick_delta;
+            if speed < self.settings.sleep_speed {
+                point.calm_time += tick_delta;
+            } else {
+                point.calm_time = 0.;
+            }
+            if point.calm_time < self.settings.sleep_delay {
+                restless[roots[index]] = true;
+            }
+        }
+        for root in self.driven_roots(roots) {
+            restless[root] = true;
+        }
+
+        for (index, point) in self.points.iter_mut().enumerate() {
+            if !point.asleep && !restless[roots[index]] {
+                point.asleep = true;
+                point.prev_position = point.position;
+                point.last_tick_position = point.position;
+            }
+        }
+    }
+
+    /// Sets the length of every stick driven by a muscle for the current time,
+    /// along with the force each stick is limited to.
+    fn actuate_sticks(&mut self) {
+        self.stick_force_limits.clear();
+        self.stick_force_limits
+            .resize(self.sticks.len(), f32::INFINITY);
+
+        for (index, muscle) in &self.muscles {
+            let Some(stick) = self.sticks.get_mut(*index) else {
+                continue;
+            };
+
+            if let Some(length) = muscle.length_at(self.time) {
+                stick.length = length.max(0.);
+            }
+            if let Some(max_force) = muscle.max_force {
+                self.stick_force_limits[*index] = max_force.max(0.);
+            }
+        }
+    }
+
+    /// Integrates every unlocked point forward by `dt` seconds.
+    fn update_points(&mut self, dt: f32) -> f32 {
+        let settings = self.settings;
+        let panel_forces = self.panel_forces(dt);
+        // The corners of panels catch the wind through their panels
+        let mut on_panel = vec![false; self.points.len()];
+        for panel in &self.panels {
+            for corner in [panel.a, panel.b, panel.c] {
+                if let Some(on_panel) = on_panel.get_mut(corner) {
+                    *on_panel = true;
+                }
+            }
+        }
+        let spring_forces = self.spring_forces(dt);
+
+        // Where each kinematic point should be at the end of the step
+        let mut targets = vec![None; self.points.len()];
+        for (index, path) in &self.kinematic_paths {
+            if let Some(target) = targets.get_mut(*index) {
+                *target = path.position_at(self.time + dt as f64);
+            }
+        }
+
+        // Randomize the order in which points are updated
+        let mut points: Vec<_> = self.points.iter_mut().enumerate().collect();
+        points.shuffle(&mut self.rng);
+
+        // Create a value to serve as the maximum distance change of all points.
+        // This will be used to see if rendering needs to take place.
+        let mut max_delta: f32 = 0.0;
+
+        for (index, point) in points {
+            // Sleeping points stay where they are until they're woken
+            if point.asleep {
+                continue;
+            }
+            // Locked points should remain stationary unless they're following a path
+            if point.locked {
+                point.prev_position = point.position;
+                if let Some(target) = targets[index] {
+                    point.position = target;
+                    max_delta = max_delta.max(point.position.distance(point.prev_position));
+                }
+                continue;
+            }
+            // Store the current position to compare state change after update.
+            let previous_position = point.position;
+
+            let gravity = gravity_at(&self.gravity_zones, settings.gravity, point.position);
+            let field_forces = field_force_at(
+                &self.force_fields,
+                point.position,
+                self.time,
+                on_panel[index],
+            ) + panel_forces[index]
+                + spring_forces[index];
+            point.update_properties(dt, gravity, field_forces, &settings);
+
+            max_delta = max_delta.max(point.position.distance(previous_position));
+        }
+
+        max_delta
+    }
+
+    /// The force the air applies to each point through the panels it's a corner of,
+    /// from the velocity the panels moved at over the last step of `dt` seconds.
+    fn panel_forces(&self, dt: f32) -> Vec<Vec3> {
+        let mut forces = vec![Vec3::ZERO; self.points.len()];
+
+        for panel in &self.panels {
+            let (Some(a), Some(b), Some(c)) = (
+                self.points.get(panel.a),
+                self.points.get(panel.b),
+                self.points.get(panel.c),
+            ) else {
+                continue;
+            };
+
+            let positions = [a.position, b.position, c.position];
+            let velocity =
+                (a.calculate_velocity() + b.calculate_velocity() + c.calculate_velocity())
+                    / (3. * dt);
+            let center = (positions[0] + positions[1] + positions[2]) / 3.;
+            let air_velocity = air_velocity_at(
+                &self.force_fields,
+                center,
+                self.time,
+                self.settings.air_density,
+            );
+
+            // Share the force evenly between the corners
+            let force = panel.aerodynamic_force(
+                positions,
+                velocity,
+                air_velocity,
+                self.settings.air_density,
+            ) / 3.;
+            forces[panel.a] += force;
+            forces[panel.b] += force;
+            forces[panel.c] += force;
+        }
+
+        forces
+    }
+
+    /// The force each point feels from the springs it's attached to,
+    /// from the velocity the points moved at over the last step of `dt` seconds.
+    fn spring_forces(&self, dt: f32) -> Vec<Vec3> {
+        let mut forces = vec![Vec3::ZERO; self.points.len()];
+
+        for spring in &self.springs {
+            let (Some(p1), Some(p2)) = (
+                self.points.get(spring.point1),
+                self.points.get(spring.point2),
+            ) else {
+                continue;
+            };
+
+            let force = spring.force(
+                p1.position,
+                p2.position,
+                p1.calculate_velocity() / dt,
+                p2.calculate_velocity() / dt,
+            );
+            forces[spring.point1] += force;
+            forces[spring.point2] -= force;
+        }
+
+        forces
+    }
+
+    fn converge(&mut self, dt: f32, connected: &HashSet<(usize, usize)>) -> f32 {
+        let mut max_delta: f32 = 0.0;
+
+        for _ in 0..self.settings.converge_iterations {
+            // first mutate all points
+            max_delta = max_delta.max(self.constrain_points());
+            max_delta = max_delta.max(self.collide_points());
+            // then adjust sticks
+            max_delta = max_delta.max(self.restore_stick_constraints(dt));
+            // then straighten bends
+            max_delta = max_delta.max(self.restore_bend_constraints(dt, connected));
+            // then inflate closed surfaces
+            max_delta = max_delta.max(self.restore_pressure_constraints(dt));
+            // then pull rigid bodies back into shape
+            max_delta = max_delta.max(self.restore_shape_matches());
+            // and finally push apart colliding points
+            if self.settings.point_collisions {
+                let pairs = overlapping_points(&self.points, connected, &mut self.point_hash);
+                max_delta = max_delta.max(separate_points(&mut self.points, &pairs));
+            }
+            // along with any points and sticks caught in solid sticks
+            if self.settings.stick_collisions {
+                self.stick_hash.reset(self.settings.spatial_hash_cell_size);
+                hash_sticks(&self.points, &self.sticks, &mut self.stick_hash);
+                let pairs = overlapping_stick_points(&self.points, &self.sticks, &self.stick_hash);
+                max_delta = max_delta.max(separate_stick_points(
+                    &mut self.points,
+                    &self.sticks,
+                    &pairs,
+                ));
+                let pairs = overlapping_sticks(&self.points, &self.sticks, &self.stick_hash);
+                max_delta = max_delta.max(separate_sticks(&mut self.points, &self.sticks, &pairs));
+            }
+        }
+
+        max_delta
+    }
+
+    /// Keeps every point inside the simulation bounds, handling each side by its `BoundaryMode`.
+    fn constrain_points(&mut self) -> f32 {
+        let settings = self.settings;
+        let bounds = settings.simulation_bounds;
+
+        // Randomize the order in which points are updated
+        let mut points: Vec<_> = self.points.iter_mut().enumerate().collect();
+        points.shuffle(&mut self.rng);
+
+        let mut max_delta: f32 = 0.0;
+
+        let width = bounds.x.1;
+        let height = bounds.y.1;
+        let depth = bounds.z.1;
+        let half_width = width * 0.5;
+        let half_depth = depth * 0.5;
+        let rectangle = bounds.shape == BoundsShape::Rectangle;
+        let x_bounds_enabled = bounds.x.0 && rectangle;
+        let y_bounds_enabled = bounds.y.0 && rectangle;
+        // The sphere already bounds every axis
+        let z_bounds_enabled = bounds.z.0 && bounds.shape != BoundsShape::Sphere;
+
+        for (index, pt) in points {
+            // Locked points aren't moved by the bounds, and keep the velocity of any path they follow
+            if pt.locked || pt.asleep {
+                continue;
+            }
+            // Store the current position to compare state change after update.
+            let previous_position = pt.position;
+
+            // Each side that's been passed: the direction back inside, how far outside the point is,
+            // the distance to the opposite side and how the side behaves.
+            // A point can only be past one side along each axis, and the outline.
+            let vertical = if !y_bounds_enabled {
+                None
+            } else if pt.position.y <= 0. {
+                // The floor
+                Some((Vec3::Y, -pt.position.y, height, bounds.modes.bottom))
+            } else if pt.position.y >= height {
+                Some((
+                    Vec3::NEG_Y,
+                    pt.position.y - height,
+                    height,
+                    bounds.modes.top,
+                ))
+            } else {
+                None
+            };
+            let horizontal = if !x_bounds_enabled {
+                None
+            } else if pt.position.x <= -half_width {
+                // The left and right walls
+                Some((
+                    Vec3::X,
+                    -half_width - pt.position.x,
+                    width,
+                    bounds.modes.left,
+                ))
+            } else if pt.position.x >= half_width {
+                Some((
+                    Vec3::NEG_X,
+                    pt.position.x - half_width,
+                    width,
+                    bounds.modes.right,
+                ))
+            } else {
+                None
+            };
+            let deep = if !z_bounds_enabled {
+                None
+            } else if pt.position.z <= -half_depth {
+                Some((
+                    Vec3::Z,
+                    -half_depth - pt.position.z,
+                    depth,
+                    bounds.modes.back,
+                ))
+            } else if pt.position.z > half_depth {
+                Some((
+                    Vec3::NEG_Z,
+                    pt.position.z - half_depth,
+                    depth,
+                    bounds.modes.front,
+                ))
+            } else {
+                None
+            };
+            // The outline has no opposite side to wrap to
+            let outline = bounds
+                .shape
+                .contact(pt.position, width, height)
+                .map(|(normal, distance)| (normal, distance, 0., bounds.modes.outline));
+
+            for (normal, distance, span, mode) in
+                [vertical, horizontal, deep, outline].into_iter().flatten()
+            {
+                if !hit_boundary(pt, normal, distance, span, mode, &settings) {
+                    self.despawned_points.push((index, DespawnReason::Boundary));
+                }
+            }
+
+            max_delta = max_delta.max(pt.position.distance(previous_position));
+        }
+
+        max_delta
+    }
+
+    /// Sweeps every point from its previous position to its current one and moves it back to
+    /// the first side of the bounds or collider it passes, reflecting what's left of its
+    /// motion off that surface. Points already touching something are left to the converge.
+    fn sweep_points(&mut self) {
+        let settings = self.settings;
+        let sides = bound_sides(&settings);
+
+        for point in self
+            .points
+            .iter_mut()
+            .filter(|point| !point.locked && !point.asleep)
+        {
+            let start = point.prev_position;
+            let end = point.position;
+            let velocity = end - start;
+            if velocity.length_squared() <= f32::EPSILON {
+                continue;
+            }
+
+            // The first surface the point reaches and the velocity it leaves it with
+            let mut first: Option<(f32, Vec3)> = None;
+            let mut keep_first = |time: f32, velocity: Vec3| {
+                if first.is_none_or(|(first_time, _)| time < first_time) {
+                    first = Some((time, velocity));
+                }
+            };
+
+            for (normal, offset, mode) in &sides {
+                if *mode == BoundaryMode::Wrap {
+                    continue;
+                }
+                let (from, to) = (start.dot(*normal) - offset, end.dot(*normal) - offset);
+                if from > 0. && to < 0. {
+                    if let Some(bounced) = impact_velocity(velocity, *normal, *mode, &settings) {
+                        keep_first(from / (from - to), bounced);
+                    }
+                }
+            }
+
+            if let Some((time, normal)) = sweep_outline(&settings, start, end) {
+                let mode = settings.simulation_bounds.modes.outline;
+                if let Some(bounced) = impact_velocity(velocity, normal, mode, &settings) {
+                    keep_first(time, bounced);
+                }
+            }
+
+            for collider in &self.colliders {
+                if let Some((time, normal)) = collider.sweep(start, end, point.radius) {
+                    keep_first(time, reflect(velocity, normal, collider.restitution));
+                }
+            }
+
+            // Carry on from the surface with the rest of the substep's motion
+            if let Some((time, bounced)) = first {
+                let impact = start + velocity * time;
+                point.position = impact + bounced * (1. - time);
+                point.prev_position = point.position - bounced;
+            }
+        }
+    }
+
+    /// Pushes every point out of the colliders it has sunk into.
+    fn collide_points(&mut self) -> f32 {
+        let mut max_delta: f32 = 0.0;
+
+        for point in self
+            .points
+            .iter_mut()
+            .filter(|point| !point.locked && !point.asleep)
+        {
+            for collider in &self.colliders {
+                max_delta = max_delta.max(collider.resolve(point));
+            }
+        }
+
+        max_delta
+    }
+
+    /// Pulls the endpoints of every stick back to the stick's length, weighted by their masses.
+    ///
+    /// Sticks are solved as XPBD distance constraints so a compliant stick stretches by the
+    /// same amount regardless of the step size or the number of converge iterations.
+    fn restore_stick_constraints(&mut self, dt: f32) -> f32 {
+        let mut max_delta: f32 = 0.0;
+
+        for (index, stick) in self.sticks.iter().enumerate() {
+            let (Some(&p1), Some(&p2)) =
+                (self.points.get(stick.point1), self.points.get(stick.point2))
+            else {
+                continue;
+            };
+            // Sleeping bodies are already at rest
+            if p1.asleep && p2.asleep {
+                continue;
+            }
+            // Split the correction by inverse mass so heavier points move less.
+            // Locked points have no inverse mass and remain stationary.
+            let w1 = p1.inverse_mass();
+            let w2 = p2.inverse_mass();
+            let total_weight = w1 + w2;
+            if total_weight <= 0. {
+                continue;
+            }
+
+            let delta = p2.position - p1.position;
+            let current_len = delta.length();
+            if current_len <= f32::EPSILON {
+                continue;
+            }
+            let direction = delta / current_len;
+
+            // Slack sticks don't need any correction
+            let Some(target_len) = stick.target_length(current_len) else {
+                continue;
+            };
+            // How far the stick is from its target length
+            let constraint = current_len - target_len;
+            // The compliance scaled to this step
+            let alpha = stick.compliance / (dt * dt);
+
+            // Muscles stall once they reach the most force they can give
+            let force_limit = self.stick_force_limits[index] * dt * dt;
+
+            let lambda = &mut self.stick_lambdas[index];
+            let delta_lambda = (-constraint - alpha * *lambda) / (total_weight + alpha);
+            let clamped_lambda = (*lambda + delta_lambda).clamp(-force_limit, force_limit);
+            let delta_lambda = clamped_lambda - *lambda;
+            *lambda = clamped_lambda;
+
+            let p1_offset = -direction * (w1 * delta_lambda);
+            let p2_offset = direction * (w2 * delta_lambda);
+
+            self.points[stick.point1].position += p1_offset;
+            self.points[stick.point2].position += p2_offset;
+
+            max_delta = max_delta.max(p1_offset.length()).max(p2_offset.length());
+        }
+
+        max_delta
+    }
+
+    /// Turns the points of every bend back towards the bend's rest angle, weighted by their masses.
+    /// Bends only act while sticks or springs still connect both of their arms, so cut or broken
+    /// chains fall apart freely.
+    fn restore_bend_constraints(&mut self, dt: f32, connected: &HashSet<(usize, usize)>) -> f32 {
+        let mut max_delta: f32 = 0.0;
+
+        for (index, bend) in self.bends.iter().enumerate() {
+            if !connected.contains(&ordered_pair(bend.start, bend.middle))
+                || !connected.contains(&ordered_pair(bend.middle, bend.end))
+            {
+                continue;
+            }
+
+            let (Some(&start), Some(&middle), Some(&end)) = (
+                self.points.get(bend.start),
+                self.points.get(bend.middle),
+                self.points.get(bend.end),
+            ) else {
+                continue;
+            };
+            if start.asleep && middle.asleep && end.asleep {
+                continue;
+            }
+
+            let arm1 = start.position - middle.position;
+            let arm2 = end.position - middle.position;
+            let arm1_len = arm1.length();
+            let arm2_len = arm2.length();
+            if arm1_len <= f32::EPSILON || arm2_len <= f32::EPSILON {
+                continue;
+            }
+            let arm1_dir = arm1 / arm1_len;
+            let arm2_dir = arm2 / arm2_len;
+
+            let cos = arm1_dir.dot(arm2_dir).clamp(-1., 1.);
+            let sin = arm1_dir.cross(arm2_dir).length();
+            // The bending direction is undefined when the points are exactly in line
+            if sin <= f32::EPSILON {
+                continue;
+            }
+            let angle = sin.atan2(cos);
+
+            // Gradients of the angle with respect to each point
+            let start_gradient = -(arm2_dir - arm1_dir * cos) / (sin * arm1_len);
+            let end_gradient = -(arm1_dir - arm2_dir * cos) / (sin * arm2_len);
+            let middle_gradient = -(start_gradient + end_gradient);
+
+            let w_start = start.inverse_mass();
+            let w_middle = middle.inverse_mass();
+            let w_end = end.inverse_mass();
+            let total_weight = w_start * start_gradient.length_squared()
+                + w_middle * middle_gradient.length_squared()
+                + w_end * end_gradient.length_squared();
+
+            // How far the joint is from its rest angle
+            let constraint = angle - bend.angle;
+            // The compliance scaled to this step
+            let alpha = bend.compliance / (dt * dt);
+            if total_weight + alpha <= 0. {
+                continue;
+            }
+
+            let lambda = &mut self.bend_lambdas[index];
+            let delta_lambda = (-constraint - alpha * *lambda) / (total_weight + alpha);
+            *lambda += delta_lambda;
+
+            let start_offset = start_gradient * (w_start * delta_lambda);
+            let middle_offset = middle_gradient * (w_middle * delta_lambda);
+            let end_offset = end_gradient * (w_end * delta_lambda);
+
+            self.points[bend.start].position += start_offset;
+            self.points[bend.middle].position += middle_offset;
+            self.points[bend.end].position += end_offset;
+
+            max_delta = max_delta
+                .max(start_offset.length())
+                .max(middle_offset.length())
+                .max(end_offset.length());
+        }
+
+        max_delta
+    }
+
+    /// Pushes the points of every pressure out or in towards the pressure's target volume,
+    /// weighted by their masses.
+    fn restore_pressure_constraints(&mut self, dt: f32) -> f32 {
+        let mut max_delta: f32 = 0.0;
+
+        for (index, pressure) in self.pressures.iter().enumerate() {
+            let Some(points) = pressure
+                .points
+                .iter()
+                .map(|&point| self.points.get(point).copied())
+                .collect::<Option<Vec<Point>>>()
+            else {
+                continue;
+            };
+
+            if points.iter().all(|point| point.asleep) {
+                continue;
+            }
+
+            let positions: Vec<Vec3> = points.iter().map(|point| point.position).collect();
+            let gradients = pressure.surface.gradients(&positions);
+
+            let total_weight: f32 = points
+                .iter()
+                .zip(&gradients)
+                .map(|(point, gradient)| point.inverse_mass() * gradient.length_squared())
+                .sum();
+
+            // How far the surface is from its target volume
+            let constraint =
+                pressure.surface.volume(&positions) - pressure.rest_volume * pressure.pressure;
+            // The compliance scaled to this step
+            let alpha = pressure.compliance / (dt * dt);
+            if total_weight + alpha <= f32::EPSILON {
+                continue;
+            }
+
+            let lambda = &mut self.pressure_lambdas[index];
+            let delta_lambda = (-constraint - alpha * *lambda) / (total_weight + alpha);
+            *lambda += delta_lambda;
+
+            for ((&point_index, point), gradient) in
+                pressure.points.iter().zip(&points).zip(&gradients)
+            {
+                let offset = *gradient * (point.inverse_mass() * delta_lambda);
+                self.points[point_index].position += offset;
+                max_delta = max_delta.max(offset.length());
+            }
+        }
+
+        max_delta
+    }
+
+    /// Pulls the points of every shape match towards the best-fit placement of its rest shape.
+    ///
+    /// The stiffness is spread over the converge iterations so a body ends up as stiff
+    /// regardless of how many there are.
+    fn restore_shape_matches(&mut self) -> f32 {
+        let mut max_delta: f32 = 0.0;
+        let iterations = self.settings.converge_iterations.max(1) as f32;
+
+        for shape_match in self.shape_matches.iter_mut() {
+            if shape_match.points.len() != shape_match.rest_offsets.len() {
+                continue;
+            }
+            let Some(points) = shape_match
+                .points
+                .iter()
+                .map(|&point| self.points.get(point).copied())
+                .collect::<Option<Vec<Point>>>()
+            else {
+                continue;
+            };
+
+            if points.iter().all(|point| point.asleep) {
+                continue;
+            }
+
+            let positions: Vec<Vec3> = points.iter().map(|point| point.position).collect();
+            let masses: Vec<f32> = points.iter().map(|point| point.mass.max(0.)).collect();
+            let (rotation, center) = shape_match.best_fit(&positions, &masses);
+            shape_match.rotation = rotation;
+
+            let stiffness = 1. - (1. - shape_match.stiffness.clamp(0., 1.)).powf(1. / iterations);
+
+            for ((&point_index, point), rest_offset) in shape_match
+                .points
+                .iter()
+                .zip(&points)
+                .zip(&shape_match.rest_offsets)
+            {
+                if point.locked {
+                    continue;
+                }
+
+                let goal = center + rotation * *rest_offset;
+                let offset = (goal - point.position) * stiffness;
+                self.points[point_index].position += offset;
+                max_delta = max_delta.max(offset.length());
+            }
+        }
+
+        max_delta
+    }
+
+    /// Despawns every point outside the `kill_volume`, along with the points it's connected to
+    /// by sticks and springs, so runaway bodies don't leave dangling halves behind.
+    fn despawn_escaped_points(&mut self) {
+        let kill_volume = self.settings.kill_volume;
+        let escaped = |index: usize| {
+            self.points
+                .get(index)
+                .is_some_and(|point| !kill_volume.contains(point.position))
+        };
+
+        let connections = self
+            .sticks
+            .iter()
+            .map(|stick| (stick.point1, stick.point2))
+            .chain(
+                self.springs
+                    .iter()
+                    .map(|spring| (spring.point1, spring.point2)),
+            );
+        let mut killed: Vec<usize> = connections
+            .filter(|&(a, b)| escaped(a) || escaped(b))
+            .flat_map(|(a, b)| [a, b])
+            .chain((0..self.points.len()).filter(|&index| escaped(index)))
+            .collect();
+        killed.sort_unstable();
+        killed.dedup();
+
+        self.despawned_points.extend(
+            killed
+                .into_iter()
+                .map(|index| (index, DespawnReason::KillVolume)),
+        );
+    }
+
+    /// Removes every stick stretched past its `max_strain`, recording it in `broken_sticks`.
+    /// Muscles go with their sticks and the rest are moved to their sticks' new indices.
+    fn break_overstrained_sticks(&mut self) {
+        let points = &self.points;
+        let broken_sticks = &mut self.broken_sticks;
+
+        let mut index = 0;
+        self.sticks.retain(|stick| {
+            let stick_index = index;
+            index += 1;
+
+            let (Some(max_strain), Some(p1), Some(p2)) = (
+                stick.max_strain,
+                points.get(stick.point1),
+                points.get(stick.point2),
+            ) else {
+                return true;
+            };
+
+            let strain = stick.strain(p1.position, p2.position);
+            if strain <= max_strain {
+                return true;
+            }
+
+            broken_sticks.push(BrokenStick {
+                index: stick_index,
+                stick: *stick,
+                strain,
+            });
+            false
+        });
+
+        if self.broken_sticks.is_empty() {
+            return;
+        }
+        let broken_sticks = &self.broken_sticks;
+        self.muscles.retain_mut(|(stick, _)| {
+            if broken_sticks.iter().any(|broken| broken.index == *stick) {
+                return false;
+            }
+
+            *stick -= broken_sticks
+                .iter()
+                .filter(|broken| broken.index < *stick)
+                .count();
+            true
+        });
+    }
+
+    /// Shaves off a fraction of each point's discrete acceleration spike.
+    fn filter(&mut self) {
+        // how aggressively to shave off the discrete acceleration “spike”
+        let jerk_damp: f32 = self.settings.jerk_damping;
+
+        for pt in self.points.iter_mut() {
+            if pt.locked || pt.asleep {
+                continue;
+            }
+
+            // t–1 -> the previous position
+            let p_nm1 = pt.last_tick_position;
+            // t -> the current position
+            let p_n = pt.prev_position;
+            // t+1 -> The next position
+            let p_np1 = pt.position;
+
+            // compute the second finite difference:
+            let delta2 = p_np1 - 2.0 * p_n + p_nm1;
+            // subtract off a fraction of it:
+            let damped = p_np1 - delta2 * jerk_damp;
+
+            pt.position = damped;
+        }
+    }
+}
+
+/// Why a point was despawned by the simulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DespawnReason {
+    /// The point reached a side of the bounds set to `BoundaryMode::Despawn`.
+    Boundary,
+    /// The point left the `kill_volume`, or was connected to a point that did.
+    KillVolume,
+}
+
+/// A stick that was stretched past its `max_strain` and removed from the world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrokenStick {
+    /// The index the stick had in `VerletWorld::sticks` before the step that broke it.
+    pub index: usize,
+    pub stick: Stick<usize>,
+    /// The strain the stick broke at.
+    pub strain: f32,
+}
+
+/// Creates a generator from the seed, or from system entropy if there is none.
+fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// The flat sides of the bounds that stop points, as the direction back inside and the
+/// offset of the side along it. Positions inside have a `normal.dot(position)` above the offset.
+fn bound_sides(settings: &SimulationSettings) -> Vec<(Vec3, f32, BoundaryMode)> {
+    let bounds = settings.simulation_bounds;
+    let rectangle = bounds.shape == BoundsShape::Rectangle;
+    let half_width = bounds.x.1 * 0.5;
+    let height = bounds.y.1;
+    let half_depth = bounds.z.1 * 0.5;
+
+    let mut sides = Vec::new();
+    if bounds.y.0 && rectangle {
+        sides.push((Vec3::Y, 0., bounds.modes.bottom));
+        sides.push((Vec3::NEG_Y, -height, bounds.modes.top));
+    }
+    if bounds.x.0 && rectangle {
+        sides.push((Vec3::X, -half_width, bounds.modes.left));
+        sides.push((Vec3::NEG_X, -half_width, bounds.modes.right));
+    }
+    if bounds.z.0 && bounds.shape != BoundsShape::Sphere {
+        sides.push((Vec3::Z, -half_depth, bounds.modes.back));
+        sides.push((Vec3::NEG_Z, -half_depth, bounds.modes.front));
+    }
+
+    sides
+}
+
+/// Where a point moving from `start` to `end` crosses the outline of a non-rectangular bounds
+/// shape, and the direction back inside there.
+fn sweep_outline(settings: &SimulationSettings, start: Vec3, end: Vec3) -> Option<(f32, Vec3)> {
+    let bounds = settings.simulation_bounds;
+    let outside = |position: Vec3| bounds.shape.contact(position, bounds.x.1, bounds.y.1);
+    if outside(start).is_some() {
+        return None;
+    }
+    let mut normal = outside(end)?.0;
+
+    // The outline is convex, so search the motion for the crossing
+    let (mut inside, mut beyond) = (0., 1.);
+    for _ in 0..OUTLINE_SWEEP_ITERATIONS {
+        let middle = (inside + beyond) * 0.5;
+        match outside(start.lerp(end, middle)) {
+            Some((middle_normal, _)) => {
+                beyond = middle;
+                normal = middle_normal;
+            }
+            None => inside = middle,
+        }
+    }
+
+    Some((beyond, normal))
+}
+
+/// The velocity a point leaves a side of the bounds with after hitting it, or `None` if the
+/// side doesn't stop points.
+fn impact_velocity(
+    velocity: Vec3,
+    normal: Vec3,
+    mode: BoundaryMode,
+    settings: &SimulationSettings,
+) -> Option<Vec3> {
+    match mode {
+        // These sides are handled once the point is past them
+        BoundaryMode::Open | BoundaryMode::Despawn => None,
+        // Only the outlines bounce wrapping points, the flat sides carry them across
+        BoundaryMode::Bounce | BoundaryMode::Wrap => {
+            Some(reflect(velocity, normal, settings.coeff_restitution))
+        }
+        BoundaryMode::Sticky => Some(Vec3::ZERO),
+        BoundaryMode::Absorb => Some(velocity - normal * velocity.dot(normal).min(0.)),
+    }
+}
+
+/// Bounces any part of `velocity` going into a surface back out with `restitution` of its speed.
+fn reflect(velocity: Vec3, normal: Vec3, restitution: f32) -> Vec3 {
+    let normal_speed = velocity.dot(normal);
+    if normal_speed < 0. {
+        velocity - normal * normal_speed * (1. + restitution)
+    } else {
+        velocity
+    }
+}
+
+/// Moves a point that's `distance` past a side of the bounds according to the side's mode,
+/// applying friction along any side it stays in contact with.
+/// `normal` points back inside and `span` is the distance to the opposite side.
+///
+/// Returns false if the point should be despawned.
+fn hit_boundary(
+    point: &mut Point,
+    normal: Vec3,
+    distance: f32,
+    span: f32,
+    mode: BoundaryMode,
+    settings: &SimulationSettings,
+) -> bool {
+    let velocity = coulomb_friction(
+        point.calculate_velocity(),
+        normal,
+        distance,
+        settings.static_friction,
+        settings.kinetic_friction,
+    );
+    let normal_speed = velocity.dot(normal);
+
+    match mode {
+        BoundaryMode::Open => {}
+        BoundaryMode::Despawn => return false,
+        BoundaryMode::Wrap if span > 0. => {
+            // Carry the whole point, velocity included, over to the opposite side
+            let offset = normal * span;
+            point.position += offset;
+            point.prev_position += offset;
+        }
+        BoundaryMode::Bounce | BoundaryMode::Wrap => {
+            // Bound the point to the side
+            point.position += normal * distance;
+            // Only bounce points still moving out
+            let bounced = if normal_speed < 0. {
+                velocity - normal * normal_speed * (1. + settings.coeff_restitution)
+            } else {
+                velocity
+            };
+            point.prev_position = point.position - bounced;
+        }
+        BoundaryMode::Sticky => {
+            point.position += normal * distance;
+            point.prev_position = point.position;
+        }
+        BoundaryMode::Absorb => {
+            point.position += normal * distance;
+            let absorbed = velocity - normal * normal_speed.min(0.);
+            point.prev_position = point.position - absorbed;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        parameters::{KillVolume, MuscleDrive, SimulationBounds},
+        solver::{
+            field::{FieldRegion, ForceKind},
+            kinematic::PathShape,
+        },
+    };
+    use bevy::math::Quat;
+
+    fn settings() -> SimulationSettings {
+        SimulationSettings {
+            seed: Some(42),
+            ..SimulationSettings::default()
+        }
+    }
+
+    fn resting_point(position: Vec3) -> Point {
+        Point::new(position, position, false)
+    }
+
+    /// A pile of colliding points with a chain running through it.
+    fn tangled_world(settings: SimulationSettings) -> VerletWorld {
+        let mut world = VerletWorld::new(SimulationSettings {
+            point_collisions: true,
+            ..settings
+        });
+        for index in 0..12 {
+            let offset = index as f32;
+            let position = Vec3::new(
+                (offset * 0.37).sin() * 0.1,
+                0.3 + offset * 0.04,
+                (offset * 0.73).cos() * 0.1,
+            );
+            let mut point = resting_point(position);
+            point.radius = 0.03;
+            world.add_point(point);
+        }
+        for index in 0..5 {
+            world.add_stick(index, index + 1);
+        }
+        world
+    }
+
+    fn trajectory(world: &mut VerletWorld, ticks: usize) -> Vec<Vec<Vec3>> {
+        (0..ticks)
+            .map(|_| {
+                world.step();
+                world.points.iter().map(|point| point.position).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_gives_identical_trajectories() {
+        let mut first = tangled_world(settings());
+        let mut second = tangled_world(settings());
+
+        assert!(trajectory(&mut first, 240) == trajectory(&mut second, 240));
+    }
+
+    #[test]
+    fn reseed_replays_the_same_trajectory() {
+        let mut world = tangled_world(settings());
+        let start = world.points.clone();
+        let first = trajectory(&mut world, 240);
+
+        world.points = start;
+        world.time = 0.;
+        world.reseed();
+        let replay = trajectory(&mut world, 240);
+
+        assert!(first == replay);
+    }
+
+    #[test]
+    fn panels_catch_the_wind_by_the_way_they_face() {
+        let wind = ForceField::new(
+            ForceKind::Wind {
+                force: Vec3::new(2., 0., 0.),
+            },
+            Vec3::ZERO,
+        );
+        let drift = |corners: [Vec3; 3]| {
+            let mut world = VerletWorld::new(SimulationSettings {
+                gravity: Vec3::ZERO,
+                ..settings()
+            });
+            world.force_fields.push(wind);
+            let [a, b, c] =
+                corners.map(|corner| world.add_point(resting_point(corner).with_mass(0.01)));
+            world.add_panel(a, b, c);
+            for _ in 0..30 {
+                world.step();
+            }
+            world.points[a].position.x - corners[0].x
+        };
+
+        // Facing the wind it's blown downwind, edge on it slices through untouched
+        let facing = drift([
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 1.5, 0.),
+            Vec3::new(0., 1., 0.5),
+        ]);
+        let edge_on = drift([
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 1.5, 0.),
+            Vec3::new(0.5, 1., 0.),
+        ]);
+        assert!(facing > 0.01);
+        assert!(edge_on.abs() < 1e-6);
+    }
+
+    #[test]
+    fn only_the_cut_island_wakes() {
+        let mut world = VerletWorld::new(SimulationSettings {
+            sleeping: true,
+            ..settings()
+        });
+        // Two separate sticks resting on the floor
+        let left = [
+            world.add_point(resting_point(Vec3::new(-0.5, 0., 0.))),
+            world.add_point(resting_point(Vec3::new(-0.4, 0., 0.))),
+        ];
+        let right = [
+            world.add_point(resting_point(Vec3::new(0.4, 0., 0.))),
+            world.add_point(resting_point(Vec3::new(0.5, 0., 0.))),
+        ];
+        world.add_stick(left[0], left[1]);
+        world.add_stick(right[0], right[1]);
+
+        for _ in 0..240 {
+            world.step();
+        }
+        assert!(world.points.iter().all(|point| point.asleep));
+
+        world.wake_islands_around(&[left[0]], &[]);
+        assert!(!world.points[left[0]].asleep && !world.points[left[1]].asleep);
+        assert!(world.points[right[0]].asleep && world.points[right[1]].asleep);
+    }
+
+    #[test]
+    fn free_point_falls_under_gravity() {
+        let mut world = VerletWorld::new(settings());
+        let start = Vec3::new(0., 1., 0.);
+        let point = world.add_point(resting_point(start));
+
+        let ticks = 30;
+        for _ in 0..ticks {
+            world.step();
+        }
+
+        // Air resistance and the jerk filter only slow the fall down
+        let elapsed = ticks as f32 * world.settings.tick_delta();
+        let free_fall = 0.5 * 9.8 * elapsed * elapsed;
+        let fallen = start.y - world.points[point].position.y;
+        assert!(fallen > 0.5 * free_fall && fallen <= free_fall * 1.01);
+        assert_eq!(world.points[point].position.x, start.x);
+        assert_eq!(world.points[point].position.z, start.z);
+        assert!(world.points[point].calculate_velocity().y < 0.);
+    }
+
+    #[test]
+    fn stick_keeps_its_length() {
+        let mut world = VerletWorld::new(settings());
+        let anchor = world.add_point(Point::new(
+            Vec3::new(0., 1.5, 0.),
+            Vec3::new(0., 1.5, 0.),
+            true,
+        ));
+        let bob = world.add_point(resting_point(Vec3::new(0.3, 1.5, 0.)));
+        world.add_stick(anchor, bob);
+
+        for _ in 0..240 {
+            world.step();
+            let length = world.points[anchor]
+                .position
+                .distance(world.points[bob].position);
+            assert!((length - 0.3).abs() < 1e-3, "stick stretched to {length}");
+        }
+
+        // The bob swung down while the locked anchor stayed put
+        assert_eq!(world.points[anchor].position, Vec3::new(0., 1.5, 0.));
+        assert!(world.points[bob].position.y < 1.5);
+    }
+
+    #[test]
+    fn paths_keep_moving_late_into_a_session() {
+        let mut world = VerletWorld::new(settings());
+        // Over a hundred days in, a single precision clock couldn't tell the ticks apart
+        world.time = 1e7;
+        let point = world.add_point(Point::new(Vec3::ZERO, Vec3::ZERO, true));
+        let mut path = KinematicPath::new(
+            PathShape::Line {
+                start: Vec3::ZERO,
+                end: Vec3::X,
+            },
+            1.,
+        );
+        path.spawn_time = world.time;
+        world.add_kinematic_path(point, path);
+
+        world.step();
+
+        let travelled = world.points[point].position.x;
+        assert!((travelled - world.settings.tick_delta()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn points_wrap_to_the_opposite_wall() {
+        let mut settings = SimulationSettings {
+            gravity: Vec3::ZERO,
+            ..settings()
+        };
+        settings.simulation_bounds.x = (true, 2.);
+        settings.simulation_bounds.modes.left = BoundaryMode::Wrap;
+        settings.simulation_bounds.modes.right = BoundaryMode::Wrap;
+        let mut world = VerletWorld::new(settings);
+        let point = world.add_point(Point::new(
+            Vec3::new(0.95, 0.5, 0.),
+            Vec3::new(0.94, 0.5, 0.),
+            false,
+        ));
+
+        for _ in 0..20 {
+            world.step();
+        }
+
+        // Carried over to the left wall, still heading right
+        let point = world.points[point];
+        assert!(point.position.x < -0.5);
+        assert!(point.calculate_velocity().x > 0.);
+        assert!(world.despawned_points.is_empty());
+    }
+
+    #[test]
+    fn despawn_sides_report_their_points() {
+        let mut settings = settings();
+        settings.simulation_bounds.modes.bottom = BoundaryMode::Despawn;
+        let mut world = VerletWorld::new(settings);
+        let point = world.add_point(resting_point(Vec3::new(0., 0.1, 0.)));
+
+        let despawned = (0..120)
+            .map(|_| {
+                world.step();
+                world.despawned_points.clone()
+            })
+            .find(|despawned| !despawned.is_empty());
+
+        assert_eq!(despawned, Some(vec![(point, DespawnReason::Boundary)]));
+    }
+
+    #[test]
+    fn kill_volume_takes_the_escaped_body_with_it() {
+        let mut settings = SimulationSettings {
+            gravity: Vec3::ZERO,
+            kill_volume: KillVolume::new(Vec3::splat(-1.), Vec3::splat(1.)),
+            ..settings()
+        };
+        settings.simulation_bounds = SimulationBounds::new(false, false, false);
+        let mut world = VerletWorld::new(settings);
+        // A stick flying out of the volume and a point sitting still inside it
+        let leading = world.add_point(Point::new(
+            Vec3::new(0.9, 0., 0.),
+            Vec3::new(0.85, 0., 0.),
+            false,
+        ));
+        let trailing = world.add_point(Point::new(
+            Vec3::new(0.7, 0., 0.),
+            Vec3::new(0.65, 0., 0.),
+            false,
+        ));
+        world.add_stick(leading, trailing);
+        world.add_point(resting_point(Vec3::ZERO));
+
+        world.step();
+        world.step();
+        world.step();
+
+        assert_eq!(
+            world.despawned_points,
+            vec![
+                (leading, DespawnReason::KillVolume),
+                (trailing, DespawnReason::KillVolume)
+            ]
+        );
+    }
+
+    #[test]
+    fn gravity_zones_override_the_gravity_inside_them() {
+        let mut world = VerletWorld::new(settings());
+        world.gravity_zones.push(GravityZone::new(
+            FieldRegion::Sphere {
+                center: Vec3::new(-0.5, 1., 0.),
+                radius: 0.3,
+            },
+            Vec3::new(0., 2., 0.),
+        ));
+        let inside = world.add_point(resting_point(Vec3::new(-0.5, 1., 0.)));
+        let outside = world.add_point(resting_point(Vec3::new(0.5, 1., 0.)));
+
+        for _ in 0..10 {
+            world.step();
+        }
+
+        assert!(world.points[inside].position.y > 1.);
+        assert!(world.points[outside].position.y < 1.);
+    }
+
+    #[test]
+    fn point_stops_at_the_floor() {
+        let mut world = VerletWorld::new(settings());
+        let point = world.add_point(resting_point(Vec3::new(0., 0.5, 0.)));
+
+        for _ in 0..600 {
+            world.step();
+            assert!(world.points[point].position.y >= 0.);
+        }
+
+        // Bouncing and friction bring it to rest on the floor
+        assert!(world.points[point].position.y < 0.05);
+        assert!(world.despawned_points.is_empty());
+    }
+
+    #[test]
+    fn heavier_points_move_less_under_a_stick() {
+        let mut world = VerletWorld::new(SimulationSettings {
+            gravity: Vec3::ZERO,
+            ..settings()
+        });
+        let light = world.add_point(resting_point(Vec3::new(-0.5, 1., 0.)));
+        let heavy = world.add_point(resting_point(Vec3::new(0.5, 1., 0.)).with_mass(4.));
+        let stick = world.add_stick(light, heavy);
+        // Pull the stick to half its length
+        world.sticks[stick].length = 0.5;
+
+        world.step();
+
+        let light_moved = world.points[light].position.x + 0.5;
+        let heavy_moved = 0.5 - world.points[heavy].position.x;
+        assert!((light_moved + heavy_moved - 0.5).abs() < 1e-3);
+        assert!((light_moved - 4. * heavy_moved).abs() < 1e-3);
+    }
+
+    #[test]
+    fn compliant_sticks_stretch_the_same_at_any_substep_count() {
+        // Hanging still, a stick gives by its compliance times the weight it holds
+        let weight = 9.8;
+        let stretch = |compliance: f32, substeps: u16| {
+            let mut world = VerletWorld::new(SimulationSettings {
+                substeps,
+                jerk_damping: 0.,
+                ..settings()
+            });
+            let anchor = world.add_point(Point::new(
+                Vec3::new(0., 1.5, 0.),
+                Vec3::new(0., 1.5, 0.),
+                true,
+            ));
+            // Hung where it should settle, so it doesn't have to swing there first
+            let bob = world.add_point(resting_point(Vec3::new(0., 1. - compliance * weight, 0.)));
+            let stick = world.add_stick(anchor, bob);
+            world.sticks[stick].length = 0.5;
+            world.sticks[stick].compliance = compliance;
+            for _ in 0..240 {
+                world.step();
+            }
+            world.points[anchor]
+                .position
+                .distance(world.points[bob].position)
+                - 0.5
+        };
+
+        assert!(stretch(0., 4).abs() < 1e-3);
+        for substeps in [1, 4, 16] {
+            let stretched = stretch(0.01, substeps);
+            assert!(
+                (stretched - 0.01 * weight).abs() < 2e-3,
+                "stretched {stretched} with {substeps} substeps"
+            );
+        }
+    }
+
+    #[test]
+    fn overstrained_sticks_break_and_report_it() {
+        let mut world = VerletWorld::new(settings());
+        // Two bobs hung from stretchy sticks, only the first of which can break
+        let mut hang = |x: f32| {
+            let anchor = world.add_point(Point::new(
+                Vec3::new(x, 1.5, 0.),
+                Vec3::new(x, 1.5, 0.),
+                true,
+            ));
+            let bob = world.add_point(resting_point(Vec3::new(x, 1., 0.)));
+            let stick = world.add_stick(anchor, bob);
+            world.sticks[stick].compliance = 0.01;
+            stick
+        };
+        let fragile = hang(-0.5);
+        let sturdy = hang(0.5);
+        world.sticks[fragile].max_strain = Some(0.05);
+        world.add_muscle(sturdy, Muscle::new(MuscleDrive::Target(0.5)));
+
+        let broken = (0..60)
+            .map(|_| {
+                world.step();
+                world.broken_sticks.clone()
+            })
+            .find(|broken| !broken.is_empty())
+            .expect("the fragile stick never broke");
+
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].index, fragile);
+        assert!(broken[0].strain > 0.05);
+        // The sturdy stick and its muscle moved down into the gap
+        assert_eq!(world.sticks.len(), 1);
+        assert_eq!(world.muscles[0].0, 0);
+    }
+
+    #[test]
+    fn colliding_points_are_pushed_apart() {
+        let gap = |point_collisions: bool| {
+            let mut world = VerletWorld::new(SimulationSettings {
+                gravity: Vec3::ZERO,
+                point_collisions,
+                ..settings()
+            });
+            let [a, b] = [-0.05, 0.05].map(|x| {
+                let mut point = resting_point(Vec3::new(x, 1., 0.));
+                point.radius = 0.1;
+                world.add_point(point)
+            });
+            world.step();
+            world.points[a].position.distance(world.points[b].position)
+        };
+
+        assert!(gap(true) >= 0.2 - 1e-4);
+        assert!((gap(false) - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn points_rest_on_colliders() {
+        let mut world = VerletWorld::new(settings());
+        let center = Vec3::new(0., 0.5, 0.);
+        world.colliders.push(Collider::sphere(center, 0.2));
+        let mut point = resting_point(Vec3::new(0., 1., 0.));
+        point.radius = 0.05;
+        let point = world.add_point(point);
+
+        for _ in 0..240 {
+            world.step();
+            let distance = world.points[point].position.distance(center);
+            assert!(distance >= 0.25 - 1e-3, "sank to {distance}");
+        }
+
+        // Settled on top of the sphere rather than falling past it
+        let position = world.points[point].position;
+        assert!((position.y - 0.75).abs() < 0.01);
+        assert!(world.points[point].calculate_velocity().length() < 1e-3);
+    }
+
+    #[test]
+    fn rougher_floors_stop_sliding_points_sooner() {
+        let slide = |kinetic_friction: f32| {
+            let mut settings = SimulationSettings {
+                kinetic_friction,
+                ..settings()
+            };
+            settings.simulation_bounds.x.0 = false;
+            let mut world = VerletWorld::new(settings);
+            // Sliding along the floor at 2 m/s
+            let step = 2. * world.settings.tick_delta();
+            let point = world.add_point(Point::new(Vec3::ZERO, Vec3::new(-step, 0., 0.), false));
+            for _ in 0..600 {
+                world.step();
+            }
+            let point = world.points[point];
+            // Held in place by static friction once it stops
+            assert!(point.calculate_velocity().x.abs() < 1e-6);
+            point.position.x
+        };
+
+        let smooth = slide(0.2);
+        let rough = slide(0.6);
+        assert!(rough > 0. && rough < smooth);
+    }
+
+    #[test]
+    fn stretched_springs_settle_back_to_their_rest_length() {
+        let mut world = VerletWorld::new(SimulationSettings {
+            gravity: Vec3::ZERO,
+            ..settings()
+        });
+        let a = world.add_point(resting_point(Vec3::new(-0.3, 1., 0.)));
+        let b = world.add_point(resting_point(Vec3::new(0.3, 1., 0.)));
+        let spring = world.add_spring(a, b, 50., 2.);
+        world.springs[spring].rest_length = 0.4;
+
+        let length =
+            |world: &VerletWorld| world.points[a].position.distance(world.points[b].position);
+        let mut shortest = length(&world);
+        for _ in 0..600 {
+            world.step();
+            shortest = shortest.min(length(&world));
+        }
+
+        // It overshot on the way in, but the damping brought it to rest
+        assert!(shortest < 0.4);
+        assert!((length(&world) - 0.4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn squashed_loops_recover_their_area() {
+        let mut world = VerletWorld::new(SimulationSettings {
+            gravity: Vec3::ZERO,
+            ..settings()
+        });
+        let ring: Vec<usize> = (0..12)
+            .map(|index| {
+                let angle = index as f32 / 12. * std::f32::consts::TAU;
+                let offset = Vec3::new(angle.cos(), angle.sin(), 0.) * 0.3;
+                world.add_point(resting_point(Vec3::new(0., 1., 0.) + offset))
+            })
+            .collect();
+        let pressure = world.add_pressure(ring.clone(), PressureSurface::Loop { normal: Vec3::Z });
+        let rest_area = world.pressures[pressure].rest_volume;
+
+        // Flatten the ring to half its height
+        for &index in &ring {
+            let point = &mut world.points[index];
+            point.position.y = 1. + (point.position.y - 1.) * 0.5;
+            point.prev_position = point.position;
+        }
+        let area = |world: &VerletWorld| {
+            let positions: Vec<Vec3> = ring
+                .iter()
+                .map(|&index| world.points[index].position)
+                .collect();
+            PressureSurface::Loop { normal: Vec3::Z }.volume(&positions)
+        };
+        assert!(area(&world) < 0.6 * rest_area);
+
+        world.step();
+
+        assert!((area(&world) - rest_area).abs() < 0.01 * rest_area);
+    }
+
+    #[test]
+    fn shape_matches_pull_dented_bodies_back_into_shape() {
+        let mut world = VerletWorld::new(SimulationSettings {
+            gravity: Vec3::ZERO,
+            ..settings()
+        });
+        let corners = [
+            Vec3::new(-0.2, 0.8, 0.),
+            Vec3::new(0.2, 0.8, 0.),
+            Vec3::new(0.2, 1.2, 0.1),
+            Vec3::new(-0.2, 1.2, 0.),
+        ];
+        let body: Vec<usize> = corners
+            .iter()
+            .map(|&corner| world.add_point(resting_point(corner)))
+            .collect();
+        world.add_shape_match(body.clone(), 1.);
+
+        // Turn the body a quarter around and dent one corner
+        let turn = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+        for &index in &body {
+            let point = &mut world.points[index];
+            point.position =
+                Vec3::new(0., 1., 0.) + turn * (point.position - Vec3::new(0., 1., 0.));
+            point.prev_position = point.position;
+        }
+        world.points[body[2]].position += Vec3::new(0.1, -0.05, 0.);
+        world.points[body[2]].prev_position = world.points[body[2]].position;
+
+        world.step();
+
+        // Back in its shape, without being turned back
+        for (i, j) in [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)] {
+            let distance = world.points[body[i]]
+                .position
+                .distance(world.points[body[j]].position);
+            assert!((distance - corners[i].distance(corners[j])).abs() < 1e-3);
+        }
+        let bottom_edge = world.points[body[1]].position - world.points[body[0]].position;
+        assert!(bottom_edge.normalize().dot(Vec3::Y) > 0.99);
+    }
+
+    #[test]
+    fn solid_sticks_push_points_and_sticks_out_of_their_capsules() {
+        let barred_world = |stick_collisions: bool| {
+            let mut world = VerletWorld::new(SimulationSettings {
+                gravity: Vec3::ZERO,
+                stick_collisions,
+                ..settings()
+            });
+            // A locked bar along x
+            let [left, right] = [-0.3, 0.3].map(|x| {
+                world.add_point(Point::new(Vec3::new(x, 1., 0.), Vec3::new(x, 1., 0.), true))
+            });
+            let bar = world.add_stick(left, right);
+            world.sticks[bar].radius = 0.05;
+            world
+        };
+        // The height above the bar's spine of the point or stick dropped into it
+        let clearance = |mut world: VerletWorld, free: usize| {
+            world.step();
+            world.points[free].position.y - 1.
+        };
+
+        // A point sunk into the top of the bar
+        let with_point = |stick_collisions: bool| {
+            let mut world = barred_world(stick_collisions);
+            let mut point = resting_point(Vec3::new(0., 1.03, 0.));
+            point.radius = 0.02;
+            let point = world.add_point(point);
+            clearance(world, point)
+        };
+        assert!(with_point(true) >= 0.07 - 1e-4);
+        assert!((with_point(false) - 0.03).abs() < 1e-6);
+
+        // A free stick crossing the bar along z
+        let with_stick = |stick_collisions: bool| {
+            let mut world = barred_world(stick_collisions);
+            let [back, front] =
+                [-0.2, 0.2].map(|z| world.add_point(resting_point(Vec3::new(0., 1.04, z))));
+            let crossing = world.add_stick(back, front);
+            world.sticks[crossing].radius = 0.02;
+            clearance(world, back)
+        };
+        assert!(with_stick(true) >= 0.07 - 1e-4);
+        assert!((with_stick(false) - 0.04).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fast_points_stop_at_thin_walls_instead_of_tunneling() {
+        let fire = |continuous_collision: bool| {
+            let mut settings = SimulationSettings {
+                gravity: Vec3::ZERO,
+                continuous_collision,
+                ..settings()
+            };
+            settings.simulation_bounds.x.0 = false;
+            let mut world = VerletWorld::new(settings);
+            // A wall 2 cm thick, hit at 60 m/s which covers half a meter each tick
+            world.colliders.push(Collider::cuboid(
+                Vec3::new(0., 1., 0.),
+                Vec3::new(0.01, 1., 1.),
+                Quat::IDENTITY,
+            ));
+            let step = 60. * world.settings.tick_delta();
+            let start = Vec3::new(-0.3, 1., 0.);
+            let point = world.add_point(Point::new(start, start - Vec3::X * step, false));
+            // The furthest the point got
+            (0..10)
+                .map(|_| {
+                    world.step();
+                    world.points[point].position.x
+                })
+                .fold(f32::MIN, f32::max)
+        };
+
+        // Never made it past the near side
+        assert!(fire(true) < -0.01);
+        // Jumped clean over the wall between two ticks
+        assert!(fire(false) > 0.01);
+    }
+}
@@ -4,8 +4,14 @@ use bevy::{platform::collections::HashMap, prelude::*};
 use web_sys::wasm_bindgen::JsValue;
 
 use crate::{
-    core::parameters::{Point, Stick},
-    plugins::attachment::plugin::AttachmentPoint,
+    core::{
+        parameters::{
+            Bend, ConnectionType, Muscle, Panel, Point, Pressure, ShapeMatch, Spring, Stick,
+            StickKind,
+        },
+        solver::kinematic::KinematicPath,
+    },
+    plugins::{attachment::plugin::AttachmentPoint, render::plugin::ConnectionScale},
 };
 
 #[derive(Debug, Clone, Resource)]
@@ -22,10 +28,32 @@ impl Default for SpawnBuffer {
 #[derive(Event, Clone, Debug, PartialEq)]
 pub struct SpawnRequest {
     pub mesh_network: Vec<SpawnNode>,
+    /// Closed surfaces over the points of the mesh network, referencing the points by position.
+    /// Each surface holds the area or volume it's spawned with.
+    pub pressure: Vec<Pressure<Vec3>>,
+    /// Groups of points of the mesh network held to their spawned shape, referencing the
+    /// points by position.
+    pub shape_match: Vec<ShapeMatch<Vec3>>,
 }
 impl SpawnRequest {
     pub fn new(mesh_network: Vec<SpawnNode>) -> Self {
-        Self { mesh_network }
+        Self {
+            mesh_network,
+            pressure: Vec::new(),
+            shape_match: Vec::new(),
+        }
+    }
+
+    /// Adds a closed surface over the points at the pressure's positions.
+    pub fn with_pressure(mut self, pressure: Pressure<Vec3>) -> Self {
+        self.pressure.push(pressure);
+        self
+    }
+
+    /// Holds the points at the shape match's positions to the shape they're spawned in.
+    pub fn with_shape_match(mut self, shape_match: ShapeMatch<Vec3>) -> Self {
+        self.shape_match.push(shape_match);
+        self
     }
 }
 
@@ -94,12 +122,37 @@ pub struct SpawnNode {
     pub point_size: f32,
     /// The thickness of the connection.
     pub connection_size: Option<Vec<f32>>,
+    /// The path a locked point is animated along, the point stays put when not specified.
+    pub path: Option<KinematicPath>,
     /// The model_name for any imported model to be attached to this point.
     pub attachment: Option<String>,
     /// How to scale the generated point visually
     pub point_scale: Vec3,
     /// How to scale the generated sticks visually
     pub connection_scale: Option<Vec<Vec3>>,
+    /// The compliance (inverse stiffness) of each connection, rigid when not specified.
+    ///
+    /// Units are m/N
+    pub connection_compliance: Option<Vec<f32>>,
+    /// Which directions each connection resists, rigid when not specified.
+    pub connection_kind: Option<Vec<StickKind>>,
+    /// The strain each connection breaks at, unbreakable when not specified.
+    pub connection_max_strain: Option<Vec<Option<f32>>>,
+    /// Whether each connection is a stick or a spring, sticks when not specified.
+    /// The compliance, kind and max strain of a connection only apply to sticks.
+    pub connection_type: Option<Vec<ConnectionType>>,
+    /// The muscle driving the length of each stick connection, static when not specified.
+    pub connection_muscle: Option<Vec<Option<Muscle>>>,
+    /// Pairs of point positions this point should hold its angle between, with this point as the vertex.
+    /// The rest angle of each bend is the angle the points are spawned at.
+    pub bend: Option<Vec<(Vec3, Vec3)>>,
+    /// The compliance of each bend, rigid when not specified.
+    ///
+    /// Units are rad/(N·m)
+    pub bend_compliance: Option<Vec<f32>>,
+    /// Pairs of point positions this point forms a triangle with that catches the air.
+    /// Each triangle only needs to be listed by one of its corners.
+    pub panel: Option<Vec<(Vec3, Vec3)>>,
 }
 impl Default for SpawnNode {
     fn default() -> Self {
@@ -112,9 +165,18 @@ impl Default for SpawnNode {
             connection_mesh: None,
             point_size: 0.025,
             connection_size: None,
+            path: None,
             attachment: None,
             point_scale: Vec3::ONE,
             connection_scale: None,
+            connection_compliance: None,
+            connection_kind: None,
+            connection_max_strain: None,
+            connection_type: None,
+            connection_muscle: None,
+            bend: None,
+            bend_compliance: None,
+            panel: None,
         }
     }
 }
@@ -130,12 +192,14 @@ impl SpawnedEntity {
     }
 }
 
+/// Spawns the points of the mesh network along with their connections, bends and panels.
+/// Returns the point entities in the order of the mesh network.
 pub fn spawner(
     mesh_network: Vec<SpawnNode>,
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
-) {
+) -> Vec<Entity> {
     // Cache all MeshType -> Handle<Mesh> and MaterialType -> Handle<StandardMaterial>
     let mut mesh_handles: HashMap<MeshType, Handle<Mesh>> = HashMap::new();
     let mut material_handles: HashMap<MaterialType, Handle<StandardMaterial>> = HashMap::new();
@@ -185,9 +249,16 @@ pub fn spawner(
             MeshMaterial3d(point_material_handle),
             Transform::from_translation(spawn_node.point.position)
                 .with_scale(spawn_node.point_scale * spawn_node.point_size),
-            spawn_node.point.clone(),
+            Point {
+                radius: spawn_node.point_size * 0.5,
+                ..spawn_node.point
+            },
         ));
 
+        if let Some(path) = &spawn_node.path {
+            spawn_command.insert(path.clone());
+        }
+
         // If this point is marked as an attachment point, add that component
         if let Some(attachment_data) = &spawn_node.attachment {
             let mut hasher = DefaultHasher::new();
@@ -235,6 +306,31 @@ pub fn spawner(
             }
             &scale_values.clone()
         };
+        let connection_compliance_values = match &spawn_node.connection_compliance {
+            Some(compliance_values) => compliance_values.clone(),
+            // Default to rigid connections
+            None => vec![0.; connection_positions.len()],
+        };
+        let connection_kind_values = match &spawn_node.connection_kind {
+            Some(kind_values) => kind_values.clone(),
+            // Default to rigid connections
+            None => vec![StickKind::Rigid; connection_positions.len()],
+        };
+        let connection_max_strain_values = match &spawn_node.connection_max_strain {
+            Some(max_strain_values) => max_strain_values.clone(),
+            // Default to unbreakable connections
+            None => vec![None; connection_positions.len()],
+        };
+        let connection_type_values = match &spawn_node.connection_type {
+            Some(type_values) => type_values.clone(),
+            // Default to stick connections
+            None => vec![ConnectionType::Stick; connection_positions.len()],
+        };
+        let connection_muscle_values = match &spawn_node.connection_muscle {
+            Some(muscle_values) => muscle_values.clone(),
+            // Default to static connections
+            None => vec![None; connection_positions.len()],
+        };
 
         // Sanity checks on lengths
         assert!(
@@ -253,6 +349,26 @@ pub fn spawner(
             connection_positions.len() == connection_scale_values.len(),
             "Scale count must match connections"
         );
+        assert!(
+            connection_positions.len() == connection_compliance_values.len(),
+            "Compliance count must match connections"
+        );
+        assert!(
+            connection_positions.len() == connection_kind_values.len(),
+            "Kind count must match connections"
+        );
+        assert!(
+            connection_positions.len() == connection_max_strain_values.len(),
+            "Max strain count must match connections"
+        );
+        assert!(
+            connection_positions.len() == connection_type_values.len(),
+            "Type count must match connections"
+        );
+        assert!(
+            connection_positions.len() == connection_muscle_values.len(),
+            "Muscle count must match connections"
+        );
 
         for (connection_index, &connection_position) in connection_positions.iter().enumerate() {
             // Find the entity for this connection position
@@ -281,7 +397,7 @@ pub fn spawner(
                 let stick_material_handle =
                     material_handles[&connection_material_types[connection_index]].clone();
 
-                commands.spawn((
+                let mut connection_command = commands.spawn((
                     Mesh3d(stick_mesh_handle),
                     MeshMaterial3d(stick_material_handle),
                     Transform {
@@ -293,12 +409,36 @@ pub fn spawner(
                             connection_size_values[connection_index],
                         ) * connection_scale_values[connection_index],
                     },
-                    Stick::new(
-                        spawned_entities[parent_index].entity,
-                        connected_entity.entity,
-                        direction_vector.length(),
-                    ),
+                    ConnectionScale(connection_scale_values[connection_index].x),
                 ));
+
+                match connection_type_values[connection_index] {
+                    ConnectionType::Stick => {
+                        connection_command.insert(
+                            Stick::new(
+                                spawned_entities[parent_index].entity,
+                                connected_entity.entity,
+                                direction_vector.length(),
+                            )
+                            .with_compliance(connection_compliance_values[connection_index])
+                            .with_kind(connection_kind_values[connection_index])
+                            .with_max_strain(connection_max_strain_values[connection_index])
+                            .with_radius(connection_size_values[connection_index] * 0.5),
+                        );
+                        if let Some(muscle) = &connection_muscle_values[connection_index] {
+                            connection_command.insert(muscle.clone());
+                        }
+                    }
+                    ConnectionType::Spring { stiffness, damping } => {
+                        connection_command.insert(Spring::new(
+                            spawned_entities[parent_index].entity,
+                            connected_entity.entity,
+                            direction_vector.length(),
+                            stiffness,
+                            damping,
+                        ));
+                    }
+                }
             } else {
                 web_sys::console::log_1(&JsValue::from_str(&format!(
                     "Requested node position doesn't exist: {:?}",
@@ -307,6 +447,167 @@ pub fn spawner(
             }
         }
     }
+
+    // Spawn bends
+    for (middle_index, spawn_node) in mesh_network.iter().enumerate() {
+        let bend_positions = if let Some(positions) = &spawn_node.bend {
+            positions
+        } else {
+            continue;
+        };
+        let bend_compliance_values = match &spawn_node.bend_compliance {
+            Some(compliance_values) => compliance_values.clone(),
+            // Default to rigid bends
+            None => vec![0.; bend_positions.len()],
+        };
+
+        assert!(
+            bend_positions.len() == bend_compliance_values.len(),
+            "Compliance count must match bends"
+        );
+
+        for (bend_index, &(start_position, end_position)) in bend_positions.iter().enumerate() {
+            // Find the entities at either end of the bend
+            let start_entity = spawned_entities
+                .iter()
+                .find(|entity_info| entity_info.position == start_position);
+            let end_entity = spawned_entities
+                .iter()
+                .find(|entity_info| entity_info.position == end_position);
+
+            if let (Some(start_entity), Some(end_entity)) = (start_entity, end_entity) {
+                let middle_position = spawn_node.point.position;
+
+                commands.spawn(
+                    Bend::new(
+                        start_entity.entity,
+                        spawned_entities[middle_index].entity,
+                        end_entity.entity,
+                        Bend::<Entity>::angle_between(
+                            start_position,
+                            middle_position,
+                            end_position,
+                        ),
+                    )
+                    .with_compliance(bend_compliance_values[bend_index]),
+                );
+            } else {
+                web_sys::console::log_1(&JsValue::from_str(&format!(
+                    "Requested bend position doesn't exist: {:?}",
+                    (start_position, end_position)
+                )));
+            }
+        }
+    }
+
+    // Spawn panels
+    for (corner_index, spawn_node) in mesh_network.iter().enumerate() {
+        let panel_positions = if let Some(positions) = &spawn_node.panel {
+            positions
+        } else {
+            continue;
+        };
+
+        for &(b_position, c_position) in panel_positions {
+            // Find the entities at the other corners of the panel
+            let b_entity = spawned_entities
+                .iter()
+                .find(|entity_info| entity_info.position == b_position);
+            let c_entity = spawned_entities
+                .iter()
+                .find(|entity_info| entity_info.position == c_position);
+
+            if let (Some(b_entity), Some(c_entity)) = (b_entity, c_entity) {
+                commands.spawn(Panel::new(
+                    spawned_entities[corner_index].entity,
+                    b_entity.entity,
+                    c_entity.entity,
+                ));
+            } else {
+                web_sys::console::log_1(&JsValue::from_str(&format!(
+                    "Requested panel position doesn't exist: {:?}",
+                    (b_position, c_position)
+                )));
+            }
+        }
+    }
+
+    spawned_entities
+        .into_iter()
+        .map(|entity_info| entity_info.entity)
+        .collect()
+}
+
+/// Finds the spawned point entities at `positions`, logging any that don't exist.
+fn entities_at(
+    positions: &[Vec3],
+    mesh_network: &[SpawnNode],
+    point_entities: &[Entity],
+) -> Option<Vec<Entity>> {
+    let entities: Option<Vec<Entity>> = positions
+        .iter()
+        .map(|position| {
+            mesh_network
+                .iter()
+                .position(|spawn_node| spawn_node.point.position == *position)
+                .map(|index| point_entities[index])
+        })
+        .collect();
+
+    if entities.is_none() {
+        web_sys::console::log_1(&JsValue::from_str(&format!(
+            "Requested positions don't all exist: {:?}",
+            positions
+        )));
+    }
+
+    entities
+}
+
+/// Spawns a closed surface over the spawned points at the pressure's positions,
+/// resting at the area or volume they enclose.
+pub fn spawn_pressure(
+    pressure: &Pressure<Vec3>,
+    mesh_network: &[SpawnNode],
+    point_entities: &[Entity],
+    commands: &mut Commands,
+) {
+    if let Some(entities) = entities_at(&pressure.points, mesh_network, point_entities) {
+        commands.spawn(
+            pressure
+                .with_points(entities)
+                .with_rest_positions(&pressure.points),
+        );
+    }
+}
+
+/// Spawns a shape match over the spawned points at the shape match's positions,
+/// holding them to the shape they're spawned in.
+pub fn spawn_shape_match(
+    shape_match: &ShapeMatch<Vec3>,
+    mesh_network: &[SpawnNode],
+    point_entities: &[Entity],
+    commands: &mut Commands,
+) {
+    let Some(entities) = entities_at(&shape_match.points, mesh_network, point_entities) else {
+        return;
+    };
+    let masses: Vec<f32> = shape_match
+        .points
+        .iter()
+        .map(|position| {
+            mesh_network
+                .iter()
+                .find(|spawn_node| spawn_node.point.position == *position)
+                .map_or(1., |spawn_node| spawn_node.point.mass)
+        })
+        .collect();
+
+    commands.spawn(
+        shape_match
+            .with_points(entities)
+            .with_rest_shape(&shape_match.points, &masses),
+    );
 }
 
 pub fn material_from_descriptor(
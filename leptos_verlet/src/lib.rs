@@ -6,24 +6,44 @@ mod plugins;
 pub mod prelude {
     pub use crate::{
         core::{
-            container_bounds::LeptosResize,
+            container_bounds::{
+                BoundaryMode, BoundaryModes, BoundsPolygon, BoundsShape, LeptosResize,
+            },
             core::{VerletCanvas, VerletConfigProvider},
-            parameters::{Point, SimulationBounds, SimulationSettings},
+            parameters::{
+                Bend, ConnectionType, KillVolume, Muscle, MuscleDrive, Panel, Point, Pressure,
+                PressureSurface, ShapeMatch, SimulationBounds, SimulationSettings, Spring, Stick,
+                StickKind,
+            },
+            solver::{
+                collider::{Collider, ColliderShape},
+                field::{
+                    Falloff, FieldRegion, ForceField, ForceKind, GravityZone, GravityZoneMode,
+                },
+                kinematic::{KinematicPath, PathLooping, PathShape},
+                world::{BrokenStick, DespawnReason, VerletWorld},
+            },
             spawner::{MaterialType, MeshType, SpawnNode, SpawnRequest},
         },
         plugins::{
             asset_loader::plugin::{model_loader, model_loader_with_options},
+            collider::plugin::SpawnCollider,
+            field::plugin::{ForceFieldRequest, GravityZoneRequest},
             info::plugin::{PointInfo, SetPointInfo},
             modification::plugin::{ModificationTarget, ModifyEventType, RelativeWindowPosition},
+            muscle::plugin::SetMuscleTarget,
             play_state::plugin::SimulationPlayStateRequest,
+            simulation::plugin::{PointDespawned, StickBroken},
         },
     };
-    pub use bevy::math::{Quat, Vec3};
+    pub use bevy::math::{Quat, Vec3, curve::EaseFunction};
     pub use bevy::prelude::default;
 
     pub use crate::aliases::{
-        AssetSender, ContainerSizeSender, ModificationEventSender, ModificationTargetSender,
-        PlayStateSender, PointInfoReceiver, PointInfoSender, SpawnSender,
+        AssetSender, ColliderSender, ContainerSizeSender, ForceFieldSender, GravityZoneSender,
+        ModificationEventSender, ModificationTargetSender, MuscleTargetSender, PlayStateSender,
+        PointDespawnedReceiver, PointInfoReceiver, PointInfoSender, SpawnSender,
+        StickBrokenReceiver,
     };
 
     pub use leptos_bevy_canvas::prelude::{
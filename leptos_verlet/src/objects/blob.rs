@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::{
+    core::{
+        parameters::{Point, Pressure, SimulationSettings},
+        spawner::{SpawnNode, spawn_pressure, spawner},
+    },
+    prelude::{MaterialType, MeshType},
+};
+
+const RADIUS: f32 = 0.2;
+const POINT_COUNT: usize = 16;
+
+pub fn spawn_blob(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    point_material: MaterialType,
+    stick_material: MaterialType,
+    center: Vec3,
+    sim_settings: &Res<SimulationSettings>,
+) {
+    let stick_mesh = MeshType::Cuboid;
+    let point_mesh = MeshType::Sphere;
+
+    // the outline of the blob, counter-clockwise looking down the z axis
+    let outline: Vec<Vec3> = (0..POINT_COUNT)
+        .map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / POINT_COUNT as f32;
+            center + Vec3::new(angle.cos(), angle.sin(), 0.) * RADIUS
+        })
+        .collect();
+
+    // each point only connects to the next one around the outline,
+    // the pressure keeps it from collapsing
+    let mesh_network: Vec<SpawnNode> = outline
+        .iter()
+        .enumerate()
+        .map(|(i, &position)| SpawnNode {
+            point: Point::new(position, position, false),
+            connection: Some(vec![outline[(i + 1) % POINT_COUNT]]),
+            point_material: point_material.clone(),
+            connection_material: Some(vec![stick_material.clone()]),
+            point_mesh: point_mesh.clone(),
+            connection_mesh: Some(vec![stick_mesh.clone()]),
+            point_size: sim_settings.default_geometry_point_size,
+            connection_size: Some(vec![sim_settings.default_geometry_stick_size]),
+            connection_scale: Some(vec![Vec3::ONE]),
+            ..default()
+        })
+        .collect();
+
+    let pressure = Pressure::outline(outline, Vec3::Z);
+
+    let point_entities = spawner(mesh_network.clone(), commands, meshes, materials);
+    spawn_pressure(&pressure, &mesh_network, &point_entities, commands);
+}
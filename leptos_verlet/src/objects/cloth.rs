@@ -11,6 +11,7 @@ use crate::{
 const GRID_GAP: f32 = 0.1; // m
 const CONTROL_BAR_HEIGHT: f32 = 0.075;
 const FLOOR_OFFSET: f32 = 0.25; // m
+const AREAL_DENSITY: f32 = 0.2; // kg/m^2, a light flag fabric
 
 pub fn spawn_cloth(
     commands: &mut Commands,
@@ -65,6 +66,41 @@ pub fn spawn_cloth(
         }
     }
 
+    // hold the angle across each interior point along the rows and columns
+    let mut bends = vec![Vec::new(); positions.len()];
+    if sim_settings.default_geometry_bend_compliance.is_some() {
+        for row in 0..rows {
+            for col in 0..cols {
+                let idx = row * cols + col;
+                // horizontal bend
+                if col > 0 && col + 1 < cols {
+                    bends[idx].push((positions[idx - 1], positions[idx + 1]));
+                }
+                // vertical bend
+                if row > 0 && row + 1 < rows {
+                    bends[idx].push((positions[idx - cols], positions[idx + cols]));
+                }
+            }
+        }
+    }
+
+    // split each grid square into two triangles of fabric that catch the air
+    let mut panels = vec![Vec::new(); positions.len()];
+    for row in 0..rows.saturating_sub(1) {
+        for col in 0..cols.saturating_sub(1) {
+            let idx = row * cols + col;
+            panels[idx].push((positions[idx + 1], positions[idx + cols]));
+            panels[idx + 1].push((positions[idx + cols + 1], positions[idx + cols]));
+        }
+    }
+
+    // each point carries the fabric of one grid square, far lighter than the 1 kg default
+    // so the cloth is light enough to flutter in the air its panels catch
+    let point_mass = AREAL_DENSITY * GRID_GAP * GRID_GAP;
+    let bend_compliance = sim_settings
+        .default_geometry_bend_compliance
+        .unwrap_or_default();
+
     // turn positions + adj into Vec<SpawnNode>
     let mesh_network: Vec<SpawnNode> = positions
         .iter()
@@ -72,7 +108,7 @@ pub fn spawn_cloth(
         .map(|(i, &pos)| {
             let neighbors = &adj[i];
             SpawnNode {
-                point: Point::new(pos, pos, false),
+                point: Point::new(pos, pos, false).with_mass(point_mass),
                 connection: Some(neighbors.iter().map(|&j| positions[j]).collect()),
                 point_material: point_material.clone(),
                 point_mesh: point_mesh.clone(),
@@ -84,6 +120,9 @@ pub fn spawn_cloth(
                     neighbors.len()
                 ]),
                 connection_scale: Some(vec![Vec3::ONE; neighbors.len()]),
+                bend: Some(bends[i].clone()),
+                bend_compliance: Some(vec![bend_compliance; bends[i].len()]),
+                panel: Some(panels[i].clone()),
                 ..default()
             }
         })
@@ -1,3 +1,4 @@
+pub mod blob;
 pub mod cloth;
 pub mod cube;
 pub mod rope;
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 
 use crate::{
     core::{
-        parameters::{Point, SimulationSettings},
+        parameters::{Point, SimulationSettings, StickKind},
         spawner::{SpawnNode, spawner},
     },
     prelude::{MaterialType, MeshType},
@@ -69,6 +69,20 @@ pub fn spawn_rope(
         let locked = i == 0;
         let neighbors = &adj[i];
 
+        // Hold the angle across each interior link so the rope resists folding
+        let bend = match sim_settings.default_geometry_bend_compliance {
+            Some(_) if i > 0 && i + 1 < positions.len() => {
+                vec![(positions[i - 1], positions[i + 1])]
+            }
+            _ => Vec::new(),
+        };
+        let bend_compliance = vec![
+            sim_settings
+                .default_geometry_bend_compliance
+                .unwrap_or_default();
+            bend.len()
+        ];
+
         mesh_network.push(SpawnNode {
             point: Point::new(pos, pos, locked),
             connection: Some(neighbors.iter().map(|&j| positions[j]).collect()),
@@ -86,6 +100,10 @@ pub fn spawn_rope(
                 neighbors.len()
             ]),
             connection_scale: Some(vec![Vec3::ONE; neighbors.len()]),
+            // Links only resist stretching so the rope buckles instead of pushing
+            connection_kind: Some(vec![StickKind::Max; neighbors.len()]),
+            bend: Some(bend),
+            bend_compliance: Some(bend_compliance),
             ..default()
         });
     }
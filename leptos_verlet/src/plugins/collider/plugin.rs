@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::{
+    core::{
+        solver::collider::{Collider, ColliderShape},
+        spawner::{MaterialType, material_from_descriptor},
+    },
+    plugins::schedule::plugin::SimulationCycle,
+};
+
+/// How far a rendered plane collider reaches in every direction.
+/// The plane itself is infinite, this is only the size of the visual.
+const PLANE_HALF_SIZE: f32 = 50.;
+
+pub struct ColliderPlugin;
+impl Plugin for ColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            handle_collider_requests.in_set(SimulationCycle::Preparation1),
+        );
+    }
+}
+
+/// Sent from Leptos to add a static collider to the scene.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct SpawnCollider {
+    pub collider: Collider,
+    /// How the collider should be drawn. The collider is invisible if not specified.
+    pub material: Option<MaterialType>,
+}
+impl SpawnCollider {
+    pub fn new(collider: Collider) -> Self {
+        Self {
+            collider,
+            material: None,
+        }
+    }
+
+    pub fn with_material(mut self, material: MaterialType) -> Self {
+        self.material = Some(material);
+        self
+    }
+}
+
+fn handle_collider_requests(
+    mut event_reader: EventReader<SpawnCollider>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for event in event_reader.read() {
+        let collider = event.collider;
+        let transform = Transform {
+            translation: collider.position,
+            rotation: collider.rotation,
+            ..default()
+        };
+
+        let Some(material) = &event.material else {
+            commands.spawn((collider, transform));
+            continue;
+        };
+
+        let mesh = match collider.shape {
+            ColliderShape::Sphere { radius } => meshes.add(Sphere::new(radius)),
+            ColliderShape::Cuboid { half_extents } => {
+                meshes.add(Cuboid::from_size(half_extents * 2.))
+            }
+            ColliderShape::Capsule {
+                half_length,
+                radius,
+            } => meshes.add(Capsule3d::new(radius, half_length * 2.)),
+            ColliderShape::Plane => meshes.add(Plane3d::new(Vec3::Y, Vec2::splat(PLANE_HALF_SIZE))),
+        };
+
+        commands.spawn((
+            collider,
+            transform,
+            Mesh3d(mesh),
+            MeshMaterial3d(material_from_descriptor(material, &mut materials)),
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    core::solver::field::{ForceField, GravityZone},
+    plugins::schedule::plugin::SimulationCycle,
+};
+
+pub struct FieldPlugin;
+impl Plugin for FieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (handle_gravity_zone_requests, handle_force_field_requests)
+                .in_set(SimulationCycle::Preparation1),
+        );
+    }
+}
+
+/// Sent from Leptos to add or remove gravity zones while the simulation runs.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub enum GravityZoneRequest {
+    /// Adds a zone, replacing any existing zone with the same id.
+    Add {
+        id: u32,
+        zone: GravityZone,
+    },
+    Remove {
+        id: u32,
+    },
+    /// Removes every zone.
+    Clear,
+}
+
+/// The id a gravity zone was added with.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GravityZoneId(pub u32);
+
+/// Sent from Leptos to add or remove force fields while the simulation runs.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub enum ForceFieldRequest {
+    /// Adds a field, replacing any existing field with the same id.
+    Add {
+        id: u32,
+        field: ForceField,
+    },
+    Remove {
+        id: u32,
+    },
+    /// Removes every field.
+    Clear,
+}
+
+/// The id a force field was added with.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ForceFieldId(pub u32);
+
+fn handle_gravity_zone_requests(
+    mut event_reader: EventReader<GravityZoneRequest>,
+    mut commands: Commands,
+    zone_query: Query<(Entity, &GravityZoneId)>,
+) {
+    // Track the zones spawned by earlier requests this frame too, so repeated ids replace them
+    let mut zones: HashMap<u32, Entity> = zone_query
+        .iter()
+        .map(|(entity, zone_id)| (zone_id.0, entity))
+        .collect();
+
+    for event in event_reader.read() {
+        match event {
+            GravityZoneRequest::Add { id, zone } => {
+                let entity = commands.spawn((*zone, GravityZoneId(*id))).id();
+                if let Some(replaced) = zones.insert(*id, entity) {
+                    commands.entity(replaced).despawn();
+                }
+            }
+            GravityZoneRequest::Remove { id } => {
+                if let Some(removed) = zones.remove(id) {
+                    commands.entity(removed).despawn();
+                }
+            }
+            GravityZoneRequest::Clear => {
+                for (_, removed) in zones.drain() {
+                    commands.entity(removed).despawn();
+                }
+            }
+        }
+    }
+}
+
+fn handle_force_field_requests(
+    mut event_reader: EventReader<ForceFieldRequest>,
+    mut commands: Commands,
+    field_query: Query<(Entity, &ForceFieldId)>,
+) {
+    // Track the fields spawned by earlier requests this frame too, so repeated ids replace them
+    let mut fields: HashMap<u32, Entity> = field_query
+        .iter()
+        .map(|(entity, field_id)| (field_id.0, entity))
+        .collect();
+
+    for event in event_reader.read() {
+        match event {
+            ForceFieldRequest::Add { id, field } => {
+                let entity = commands.spawn((*field, ForceFieldId(*id))).id();
+                if let Some(replaced) = fields.insert(*id, entity) {
+                    commands.entity(replaced).despawn();
+                }
+            }
+            ForceFieldRequest::Remove { id } => {
+                if let Some(removed) = fields.remove(id) {
+                    commands.entity(removed).despawn();
+                }
+            }
+            ForceFieldRequest::Clear => {
+                for (_, removed) in fields.drain() {
+                    commands.entity(removed).despawn();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::solver::field::ForceKind;
+
+    #[test]
+    fn repeated_ids_in_a_frame_leave_a_single_field() {
+        let mut app = App::new();
+        app.add_event::<ForceFieldRequest>()
+            .add_systems(Update, handle_force_field_requests);
+
+        let wind = |x: f32| {
+            ForceField::new(
+                ForceKind::Wind {
+                    force: Vec3::new(x, 0., 0.),
+                },
+                Vec3::ZERO,
+            )
+        };
+        app.world_mut().send_event(ForceFieldRequest::Add {
+            id: 0,
+            field: wind(1.),
+        });
+        app.world_mut().send_event(ForceFieldRequest::Add {
+            id: 0,
+            field: wind(2.),
+        });
+        app.update();
+
+        let mut fields = app.world_mut().query::<&ForceField>();
+        let fields: Vec<&ForceField> = fields.iter(app.world()).collect();
+        assert_eq!(fields, vec![&wind(2.)]);
+    }
+}
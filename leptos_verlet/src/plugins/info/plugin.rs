@@ -68,6 +68,7 @@ fn receive_leptos_update(
             Ok(mut target_point) => {
                 target_point.position = event.position;
                 target_point.prev_position = target_point.position - event.velocity;
+                target_point.wake();
             }
             Err(_) => return,
         }
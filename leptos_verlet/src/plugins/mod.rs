@@ -1,7 +1,10 @@
 pub mod asset_loader;
 pub mod attachment;
+pub mod collider;
+pub mod field;
 pub mod info;
 pub mod modification;
+pub mod muscle;
 pub mod play_state;
 pub mod render;
 pub mod schedule;
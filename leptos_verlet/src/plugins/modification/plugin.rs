@@ -2,7 +2,10 @@ use bevy::prelude::*;
 
 use crate::{
     core::parameters::{SimulationSettings, Stick},
-    objects::{cloth::spawn_cloth, cube::spawn_cube, rope::spawn_rope, square::spawn_square},
+    objects::{
+        blob::spawn_blob, cloth::spawn_cloth, cube::spawn_cube, rope::spawn_rope,
+        square::spawn_square,
+    },
     plugins::{
         info::plugin::ActiveInfoTarget,
         modification::utils::{
@@ -11,6 +14,7 @@ use crate::{
         },
         play_state::plugin::SimulationPlayState,
         schedule::plugin::SimulationCycle,
+        simulation::plugin::SpatialIndex,
     },
     prelude::{MaterialType, MeshType, Point},
 };
@@ -77,6 +81,8 @@ pub enum ModificationTarget {
     SpawnCloth,
     /// Spawn a cube at a selected location.
     SpawnCube,
+    /// Spawn a pressurized blob at a selected location.
+    SpawnBlob,
     /// Right click on a point to delete it.
     Delete,
     PointInfo,
@@ -128,6 +134,7 @@ pub fn handle_modification_event(
     mut next_target: ResMut<NextState<ModificationTarget>>,
     mut line_query: Query<(Entity, &mut LineConnections)>,
     sim_settings: Res<SimulationSettings>,
+    spatial_index: Res<SpatialIndex>,
     camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
 ) {
     let point_material = MaterialType::Color([1., 1., 1., 1.]);
@@ -163,6 +170,7 @@ pub fn handle_modification_event(
                         ray,
                         &mut line_query,
                         &params.p2(),
+                        &spatial_index,
                         &mut commands,
                         &mut meshes,
                         &mut materials,
@@ -173,6 +181,7 @@ pub fn handle_modification_event(
                         lock_affected_points(
                             ray,
                             &mut params.p1(),
+                            &spatial_index,
                             &mut materials,
                             sim_settings.interaction_radius,
                         );
@@ -217,12 +226,22 @@ pub fn handle_modification_event(
                         &view_plane_world_pos,
                         &sim_settings,
                     ),
+                    ModificationTarget::SpawnBlob => spawn_blob(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        point_material.clone(),
+                        stick_material.clone(),
+                        view_plane_world_pos,
+                        &sim_settings,
+                    ),
                     ModificationTarget::PointInfo => {
                         // perge existing info targets
                         perge_info_target(&params.p3(), &mut commands);
                         point_info(
                             ray,
                             &params.p2(),
+                            &spatial_index,
                             &mut commands,
                             sim_settings.interaction_radius,
                         )
@@ -249,10 +268,16 @@ pub fn handle_modification_event(
                         ray,
                         &mut stick_query,
                         &params.p0(),
+                        &spatial_index,
                         &mut commands,
                         sim_settings.interaction_radius,
                     ),
-                    ModificationTarget::Grab => grab_point(params.p4(), ray),
+                    ModificationTarget::Grab => grab_point(
+                        params.p4(),
+                        &spatial_index,
+                        ray,
+                        sim_settings.interaction_radius,
+                    ),
 
                     _ => (),
                 }
@@ -3,7 +3,10 @@ use web_sys::wasm_bindgen::JsValue;
 
 use crate::{
     core::{parameters::Stick, spawner::material_from_descriptor},
-    plugins::{info::plugin::ActiveInfoTarget, modification::plugin::LineConnections},
+    plugins::{
+        info::plugin::ActiveInfoTarget, modification::plugin::LineConnections,
+        simulation::plugin::SpatialIndex,
+    },
     prelude::{MaterialType, Point},
 };
 
@@ -19,10 +22,13 @@ pub fn perge_info_target(
 pub fn point_info(
     cast_ray: Ray3d,
     point_query: &Query<(Entity, &Point)>,
+    index: &SpatialIndex,
     commands: &mut Commands,
     modification_radius: f32,
 ) {
-    for (entity, point) in point_query.iter() {
+    for (entity, point) in
+        point_query.iter_many(index.points_near_ray(cast_ray, modification_radius))
+    {
         if point_on_ray(&cast_ray, point.position, modification_radius) {
             commands.entity(entity).insert(ActiveInfoTarget);
         }
@@ -43,14 +49,17 @@ pub fn purge_line(line_query: &Query<(Entity, &mut LineConnections)>, commands:
 pub fn lock_affected_points(
     cast_ray: Ray3d,
     points: &mut Query<(&mut MeshMaterial3d<StandardMaterial>, &mut Point)>,
+    index: &SpatialIndex,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     modification_radius: f32,
 ) {
-    // Loop through all the spawned points that should be analyzed for selection
-    for (mut material, mut pt) in points {
+    // Loop through the spawned points near the ray that should be analyzed for selection
+    let mut nearby = points.iter_many_mut(index.points_near_ray(cast_ray, modification_radius));
+    while let Some((mut material, mut pt)) = nearby.fetch_next() {
         // Check to see if the point lies on the ray
         if point_on_ray(&cast_ray, pt.position, modification_radius) {
             pt.locked = !pt.locked;
+            pt.wake();
 
             let color = if pt.locked {
                 Color::srgb(1., 0., 0.)
@@ -68,6 +77,7 @@ pub fn spawn_stick(
     cast_ray: Ray3d,
     line_query: &mut Query<(Entity, &mut LineConnections)>,
     points: &Query<(Entity, &Point)>,
+    index: &SpatialIndex,
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
@@ -86,7 +96,7 @@ pub fn spawn_stick(
     let material = material_from_descriptor(&material, materials);
 
     // Find, optionally, the point that is at the event coordinates
-    for (entity, point) in points {
+    for (entity, point) in points.iter_many(index.points_near_ray(cast_ray, modification_radius)) {
         if point_on_ray(&cast_ray, point.position, modification_radius) {
             // Attach this entity to one of the arms of the LineConntection
             match (line.p0, line.p1) {
@@ -146,10 +156,11 @@ pub fn cut_sticks(
     cast_ray: Ray3d,
     sticks: &mut Query<(Entity, &mut Stick)>,
     points: &Query<&Point>,
+    index: &SpatialIndex,
     commands: &mut Commands,
     modification_radius: f32,
 ) {
-    for (entity, stick) in sticks {
+    for (entity, stick) in sticks.iter_many(index.sticks_near_ray(cast_ray, modification_radius)) {
         if let Ok([p1, p2]) = points.get_many([stick.point1, stick.point2]) {
             // Create the list of points between this sticks endpoints
             let sample_points =
@@ -225,37 +236,16 @@ pub fn point_on_ray(ray: &Ray3d, point: Vec3, tolerance: f32) -> bool {
     (projected - point).length_squared() <= tolerance * tolerance
 }
 
-// check for some modification distance maybe
-pub fn grab_point(mut points: Query<(Entity, &mut Point)>, ray: Ray3d) {
-    // Find the Entity of the closest point
-    let closest_ent = {
-        let dir: Vec3 = ray.direction.into();
-        let mut best: Option<(Entity, f32)> = None;
-
-        for (entity, point) in points.iter_mut() {
-            if point.locked {
-                continue;
-            }
-
-            let v = point.position - ray.origin;
-            let t = v.dot(dir);
-            if t < 0.0 {
-                continue;
-            }
-
-            let proj = ray.origin + dir * t;
-            let dist2 = (proj - point.position).length_squared();
-
-            // init or replace if this one is closer
-            match best {
-                None => best = Some((entity, dist2)),
-                Some((_, best_dist2)) if dist2 < best_dist2 => best = Some((entity, dist2)),
-                _ => {}
-            }
-        }
-
-        best.map(|(entity, _)| entity)
-    };
+/// Moves the unlocked point closest to the ray onto the view plane,
+/// if any lies within `modification_radius` of it.
+pub fn grab_point(
+    mut points: Query<(Entity, &mut Point)>,
+    index: &SpatialIndex,
+    ray: Ray3d,
+    modification_radius: f32,
+) {
+    let nearby: Vec<Entity> = index.points_near_ray(ray, modification_radius).collect();
+    let closest_ent = closest_point_to_ray(&points, nearby, ray, modification_radius);
 
     // Mutate the closest point
     if let Some(ent) = closest_ent {
@@ -266,6 +256,46 @@ pub fn grab_point(mut points: Query<(Entity, &mut Point)>, ray: Ray3d) {
             };
             pt.position = new_coords;
             pt.prev_position = new_coords;
+            pt.wake();
         }
     }
 }
+
+/// Finds the Entity of the unlocked point out of `candidates` that's closest to the ray,
+/// ignoring any further than `tolerance` from it.
+fn closest_point_to_ray(
+    points: &Query<(Entity, &mut Point)>,
+    candidates: Vec<Entity>,
+    ray: Ray3d,
+    tolerance: f32,
+) -> Option<Entity> {
+    let dir: Vec3 = ray.direction.into();
+    let mut best: Option<(Entity, f32)> = None;
+
+    for (entity, point) in points.iter_many(candidates) {
+        if point.locked {
+            continue;
+        }
+
+        let v = point.position - ray.origin;
+        let t = v.dot(dir);
+        if t < 0.0 {
+            continue;
+        }
+
+        let proj = ray.origin + dir * t;
+        let dist2 = (proj - point.position).length_squared();
+        if dist2 > tolerance * tolerance {
+            continue;
+        }
+
+        // init or replace if this one is closer
+        match best {
+            None => best = Some((entity, dist2)),
+            Some((_, best_dist2)) if dist2 < best_dist2 => best = Some((entity, dist2)),
+            _ => {}
+        }
+    }
+
+    best.map(|(entity, _)| entity)
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use crate::{
+    core::parameters::{Muscle, MuscleDrive},
+    plugins::schedule::plugin::SimulationCycle,
+};
+
+pub struct MusclePlugin;
+impl Plugin for MusclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            handle_muscle_targets.in_set(SimulationCycle::Preparation1),
+        );
+    }
+}
+
+/// Sent from Leptos to drive every muscle on a channel to a length.
+/// The muscles hold the length until it's set again, whatever drive they were spawned with.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct SetMuscleTarget {
+    pub channel: u32,
+    /// The length the muscles should pull their sticks to.
+    ///
+    /// Units are m
+    pub length: f32,
+}
+impl SetMuscleTarget {
+    pub fn new(channel: u32, length: f32) -> Self {
+        Self { channel, length }
+    }
+}
+
+fn handle_muscle_targets(
+    mut event_reader: EventReader<SetMuscleTarget>,
+    mut muscle_query: Query<&mut Muscle>,
+) {
+    for event in event_reader.read() {
+        for mut muscle in &mut muscle_query {
+            if muscle.channel == Some(event.channel) {
+                muscle.drive = MuscleDrive::Target(event.length);
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use bevy::prelude::*;
 
-use crate::core::parameters::{Point, Stick};
+use crate::{
+    core::{
+        parameters::{Bend, Panel, Point, Pressure, ShapeMatch, Spring, Stick},
+        solver::world::VerletWorld,
+    },
+    plugins::simulation::plugin::PhysicsClock,
+};
 
 #[derive(Event, Clone)]
 pub enum SimulationPlayStateRequest {
@@ -31,7 +37,14 @@ fn handle_play_state_request(
     mut event_reader: EventReader<SimulationPlayStateRequest>,
     point_query: Query<Entity, With<Point>>,
     stick_query: Query<Entity, With<Stick>>,
+    spring_query: Query<Entity, With<Spring>>,
+    bend_query: Query<Entity, With<Bend>>,
+    panel_query: Query<Entity, With<Panel>>,
+    pressure_query: Query<Entity, With<Pressure>>,
+    shape_match_query: Query<Entity, With<ShapeMatch>>,
     mesh_query: Query<Entity, With<SceneRoot>>,
+    mut world: ResMut<VerletWorld>,
+    mut clock: ResMut<PhysicsClock>,
 ) {
     for event in event_reader.read() {
         match event {
@@ -50,6 +63,26 @@ fn handle_play_state_request(
                     commands.entity(entity).despawn();
                 }
 
+                for entity in spring_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+
+                for entity in bend_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+
+                for entity in panel_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+
+                for entity in pressure_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+
+                for entity in shape_match_query.iter() {
+                    commands.entity(entity).despawn();
+                }
+
                 for entity in point_query.iter() {
                     commands.entity(entity).despawn();
                 }
@@ -58,6 +91,11 @@ fn handle_play_state_request(
                     commands.entity(entity).despawn();
                 }
 
+                // Start the next run from the same state so seeded simulations replay identically
+                world.reseed();
+                world.time = 0.;
+                *clock = PhysicsClock::default();
+
                 next_state.set(SimulationPlayState::Running)
             }
         }
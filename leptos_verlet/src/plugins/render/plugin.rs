@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    core::parameters::{Point, Stick},
+    core::parameters::{Point, Spring, Stick},
     plugins::schedule::plugin::SimulationCycle,
 };
 
@@ -10,13 +10,17 @@ impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(FrameComparison::default()).add_systems(
             Update,
-            (render_points, render_points_and_sticks)
+            (render_points, render_points_and_sticks, render_springs)
                 .chain()
                 .in_set(SimulationCycle::Render),
         );
     }
 }
 
+/// The visual scale of a stick or spring along its length, spanning its points at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct ConnectionScale(pub f32);
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Resource)]
 pub struct FrameComparison {
     /// Represents the number of frames that have ellapsed since the last render.
@@ -41,6 +45,8 @@ fn render_points(mut query: Query<(&mut Point, &mut Transform)>, state: Res<Fram
 
     for (mut point, mut transform) in &mut query {
         transform.translation = point.position;
+        // Recording what was rendered doesn't move the point, so leave it unchanged for the `SpatialIndex`
+        let point = point.bypass_change_detection();
         point.rendered_position = point.position;
         point.previously_rendered_position = point.prev_position;
     }
@@ -50,7 +56,10 @@ fn render_points(mut query: Query<(&mut Point, &mut Transform)>, state: Res<Fram
 fn render_points_and_sticks(
     point_pos_q: Query<&Point>,
     mut point_tf_q: Query<&mut Transform, (With<Point>, Without<Stick>)>,
-    mut stick_tf_q: Query<(&Stick, &mut Transform), (With<Stick>, Without<Point>)>,
+    mut stick_tf_q: Query<
+        (&Stick, &mut Transform, Option<&ConnectionScale>),
+        (With<Stick>, Without<Point>),
+    >,
     state: Res<FrameComparison>,
 ) {
     if !state.changed {
@@ -58,7 +67,7 @@ fn render_points_and_sticks(
     }
 
     // Update/rotate each stick…
-    for (stick, mut stick_tf) in stick_tf_q.iter_mut() {
+    for (stick, mut stick_tf, scale) in stick_tf_q.iter_mut() {
         let p1 = match point_pos_q.get(stick.point1) {
             Ok(point) => point,
             Err(_) => continue,
@@ -73,6 +82,8 @@ fn render_points_and_sticks(
 
         stick_tf.translation = mid;
         stick_tf.rotation = rot;
+        // Compliant, slack and muscle driven sticks don't keep their spawned length
+        stick_tf.scale.x = p1.position.distance(p2.position) * scale.map_or(1., |scale| scale.0);
 
         // …and immediately drive its endpoints’ rotations:
         if let Ok(mut pt1_tf) = point_tf_q.get_mut(stick.point1) {
@@ -83,3 +94,29 @@ fn render_points_and_sticks(
         }
     }
 }
+
+/// Render's springs based off the new point translations, stretching them to span their points.
+fn render_springs(
+    point_pos_q: Query<&Point>,
+    mut spring_tf_q: Query<(&Spring, &mut Transform, Option<&ConnectionScale>), Without<Point>>,
+    state: Res<FrameComparison>,
+) {
+    if !state.changed {
+        return;
+    }
+
+    for (spring, mut spring_tf, scale) in spring_tf_q.iter_mut() {
+        let Ok([p1, p2]) = point_pos_q.get_many([spring.point1, spring.point2]) else {
+            continue;
+        };
+
+        let delta = p2.position - p1.position;
+        let Some(direction) = delta.try_normalize() else {
+            continue;
+        };
+
+        spring_tf.translation = (p1.position + p2.position) * 0.5;
+        spring_tf.rotation = Quat::from_rotation_arc(Vec3::X, direction);
+        spring_tf.scale.x = delta.length() * scale.map_or(1., |scale| scale.0);
+    }
+}
@@ -1,19 +1,39 @@
-use bevy::{platform::collections::HashSet, prelude::*};
-use rand::{seq::SliceRandom, thread_rng};
+use bevy::{ecs::system::SystemParam, platform::collections::HashMap, prelude::*};
 
 use crate::{
     core::{
-        parameters::{Point, SimulationSettings, Stick},
-        spawner::{SpawnBuffer, SpawnRequest, spawner},
+        parameters::{
+            Bend, Muscle, Panel, Point, Pressure, ShapeMatch, SimulationSettings, Spring, Stick,
+        },
+        solver::{
+            collider::Collider,
+            field::{ForceField, GravityZone},
+            kinematic::KinematicPath,
+            spatial_hash::SpatialHash,
+            world::{DespawnReason, VerletWorld},
+        },
+        spawner::{SpawnBuffer, SpawnRequest, spawn_pressure, spawn_shape_match, spawner},
+    },
+    plugins::{
+        modification::plugin::handle_modification_event, render::plugin::FrameComparison,
+        schedule::plugin::SimulationCycle,
     },
-    plugins::{render::plugin::FrameComparison, schedule::plugin::SimulationCycle},
 };
 
 pub struct SimulationPlugin;
 impl Plugin for SimulationPlugin {
     // Verlet based on: https://www.youtube.com/watch?v=3HjO_RGIjCU
     fn build(&self, app: &mut App) {
-        app.insert_resource(SpawnBuffer::default())
+        app.add_event::<StickBroken>()
+            .add_event::<PointDespawned>()
+            .insert_resource(SpawnBuffer::default())
+            .insert_resource(PhysicsClock::default())
+            .insert_resource(VerletWorld::default())
+            .insert_resource(SpatialIndex::default())
+            .insert_resource(PendingWakes::default())
+            .add_observer(record_removed_stick)
+            .add_observer(record_removed_spring)
+            .add_observer(record_removed_point)
             .add_systems(
                 Update,
                 (handle_spawn_requests, spawn_buffer)
@@ -22,315 +42,520 @@ impl Plugin for SimulationPlugin {
             )
             .add_systems(
                 Update,
-                (despawn_overflows, update_points).in_set(SimulationCycle::Compute),
+                index_entities
+                    .before(handle_modification_event)
+                    .in_set(SimulationCycle::Preparation1),
             )
             .add_systems(
                 Update,
-                (converge, filter).chain().in_set(SimulationCycle::Converge),
+                (despawn_orphans, stamp_spawn_times, simulate)
+                    .chain()
+                    .in_set(SimulationCycle::Compute),
             );
     }
 }
 
-/// Listens for any spawn requst sent from Leptos and inserts the mesh_network into
-/// the spawn buffer for the next spawn cycle.
-fn handle_spawn_requests(
-    mut event_reader: EventReader<SpawnRequest>,
-    mut buffer: ResMut<SpawnBuffer>,
-) {
-    for event in event_reader.read() {
-        buffer.buffer.push(event.clone())
-    }
-}
-/// Spawn any mesh networks in the spawn buffer and clear out the buffer.
-fn spawn_buffer(
-    mut buffer: ResMut<SpawnBuffer>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    for mesh_network in buffer.buffer.drain(..) {
-        spawner(
-            mesh_network.mesh_network,
-            &mut commands,
-            &mut meshes,
-            &mut materials,
-        );
-    }
+/// Sent to Leptos whenever a stick is stretched past its `max_strain` and breaks.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StickBroken {
+    /// The position of the stick's first endpoint when it broke.
+    pub point1: Vec3,
+    /// The position of the stick's second endpoint when it broke.
+    pub point2: Vec3,
+    /// The strain the stick broke at.
+    pub strain: f32,
 }
 
-fn despawn_overflows(
-    stick_query: Query<(Entity, &Stick)>,
-    point_query: Query<(Entity, &Point)>,
-    mut commands: Commands,
-) {
-    const ACTUAL_SIMULATION_BOUNDS: f32 = 5_000.;
-    let mut despawned_points = HashSet::new();
+/// Sent to Leptos whenever a point is removed from the simulation by the simulation itself.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PointDespawned {
+    /// The position of the point when it was removed.
+    pub position: Vec3,
+    pub reason: DespawnReason,
+}
 
-    // First check all the sticks for points that could overflow
-    for (stick_entity, stick) in &stick_query {
-        if let Ok([p1, p2]) = point_query.get_many([stick.point1, stick.point2]) {
-            let p1_out = p1.1.position.length() >= ACTUAL_SIMULATION_BOUNDS;
-            let p2_out = p2.1.position.length() >= ACTUAL_SIMULATION_BOUNDS;
+#[derive(Debug, Clone, Default, Resource)]
+/// Buckets the point and stick entities by where they are so the modification tools only
+/// need to check the entities near the mouse.
+///
+/// Rebuilt at the start of any frame in which a point or stick moved, changed or was removed.
+pub struct SpatialIndex {
+    /// Every point, keyed by its index in `point_entities`.
+    pub points: SpatialHash,
+    /// Every stick, keyed by its index in `stick_entities`.
+    pub sticks: SpatialHash,
+    pub point_entities: Vec<Entity>,
+    pub stick_entities: Vec<Entity>,
+}
+impl SpatialIndex {
+    /// The point entities that may lie within `tolerance` of the ray.
+    pub fn points_near_ray(&self, ray: Ray3d, tolerance: f32) -> impl Iterator<Item = Entity> {
+        self.points
+            .query_ray(ray.origin, ray.direction.into(), tolerance)
+            .into_iter()
+            .map(|index| self.point_entities[index])
+    }
 
-            if p1_out || p2_out {
-                if !despawned_points.contains(&p1.0) {
-                    commands.entity(p1.0).despawn();
-                    despawned_points.insert(p1.0);
-                }
-                if !despawned_points.contains(&p2.0) {
-                    commands.entity(p2.0).despawn();
-                    despawned_points.insert(p2.0);
-                }
-            }
-        } else {
-            commands.entity(stick_entity).despawn();
-        }
+    /// The stick entities that may pass within `tolerance` of the ray.
+    pub fn sticks_near_ray(&self, ray: Ray3d, tolerance: f32) -> impl Iterator<Item = Entity> {
+        self.sticks
+            .query_ray(ray.origin, ray.direction.into(), tolerance)
+            .into_iter()
+            .map(|index| self.stick_entities[index])
     }
+}
 
-    // Check individual points that might not be
-    for (entity, point) in &point_query {
-        if point.position.length() >= ACTUAL_SIMULATION_BOUNDS
-            && !despawned_points.contains(&entity)
-        {
-            commands.entity(entity).despawn();
+#[derive(Debug, Clone, Copy, Default, Resource)]
+/// Accumulates rendered frame time so the physics can be advanced in fixed ticks.
+pub struct PhysicsClock {
+    /// Frame time that hasn't been simulated yet.
+    ///
+    /// Units are seconds
+    pub accumulator: f32,
+}
+impl PhysicsClock {
+    /// Adds the frame time to the accumulator and returns the number of ticks that should be simulated.
+    ///
+    /// If more than `max_ticks_per_frame` ticks are owed, the backlog is dropped to
+    /// keep slow frames from snowballing into even slower ones.
+    pub fn advance(&mut self, frame_delta: f32, sim_settings: &SimulationSettings) -> u32 {
+        let tick_delta = sim_settings.tick_delta();
+        self.accumulator += frame_delta;
+
+        let owed_ticks = (self.accumulator / tick_delta).floor() as u32;
+        let max_ticks = sim_settings.max_ticks_per_frame as u32;
+
+        if owed_ticks > max_ticks {
+            self.accumulator = 0.;
+            max_ticks
+        } else {
+            self.accumulator -= owed_ticks as f32 * tick_delta;
+            owed_ticks
         }
     }
 }
 
-fn update_points(
-    mut query: Query<&mut Point>,
-    time: Res<Time>,
-    mut state: ResMut<FrameComparison>,
-    sim_settings: Res<SimulationSettings>,
+/// Times the newly spawned paths and muscles from the current simulation time,
+/// so they start from the beginning however long the simulation has been running.
+fn stamp_spawn_times(
+    mut paths: Query<&mut KinematicPath, Added<KinematicPath>>,
+    mut muscles: Query<&mut Muscle, Added<Muscle>>,
+    world: Res<VerletWorld>,
 ) {
-    // Randomize the order in which points are updated
-    let mut points: Vec<_> = query.iter_mut().collect();
-    points.shuffle(&mut thread_rng());
-
-    // Create a value to serve as the maximum distance change of all points.
-    // This will be used to see if rendering needs to take place.
-    let mut max_delta: f32 = 0.0;
+    for mut path in &mut paths {
+        path.spawn_time = world.time;
+    }
+    for mut muscle in &mut muscles {
+        muscle.spawn_time = world.time;
+    }
+}
 
-    for point in points.iter_mut() {
-        // Locked points should remain stationary
-        if point.locked {
-            point.prev_position = point.position;
-            continue;
-        }
-        // Store the current position to compare state change after update.
-        let previous_position = point.position;
+/// Everything attached to the simulation points that's copied into the `VerletWorld`.
+#[derive(SystemParam)]
+struct BodyQueries<'w, 's> {
+    paths: Query<'w, 's, &'static KinematicPath>,
+    sticks: Query<'w, 's, (Entity, &'static Stick, Option<&'static Muscle>)>,
+    springs: Query<'w, 's, &'static Spring>,
+    bends: Query<'w, 's, &'static Bend>,
+    panels: Query<'w, 's, &'static Panel>,
+    pressures: Query<'w, 's, &'static Pressure>,
+    shape_matches: Query<'w, 's, (Entity, &'static mut ShapeMatch)>,
+}
 
-        point.update_properties(&time, &sim_settings);
+/// What was removed since the last simulated tick that sleeping bodies may have been leaning
+/// on or hanging from, so just their islands are woken.
+#[derive(Resource, Debug, Default)]
+struct PendingWakes {
+    /// The points at either end of the removed sticks and springs.
+    points: Vec<Entity>,
+    /// The position and radius of the removed points.
+    spheres: Vec<(Vec3, f32)>,
+}
 
-        // Compare the distances before and after updated to see how it compares to the max_delta seen so far.
-        let updated_position = point.position;
-        let delta = updated_position.distance(previous_position).abs();
-        if delta > max_delta {
-            // Update max_delta if this value is greater.
-            max_delta = delta;
-        }
+fn record_removed_stick(
+    trigger: Trigger<OnRemove, Stick>,
+    sticks: Query<&Stick>,
+    mut pending: ResMut<PendingWakes>,
+) {
+    if let Ok(stick) = sticks.get(trigger.target()) {
+        pending.points.extend([stick.point1, stick.point2]);
     }
+}
 
-    if max_delta > sim_settings.min_render_delta {
-        // Reflect the in the state that this frame needs to be rendered.
-        state.frames_since = 0;
-        state.changed = true;
-    } else if state.frames_since > sim_settings.max_unchanged_frames {
-        // Check if the frame should be rendered based on reaching it's frame limit.
-        state.frames_since = 0;
-        state.changed = true;
-    } else {
-        // If no changes happened to dictate a rerender, make it known.
-        state.frames_since += 1;
-        state.changed = false;
+fn record_removed_spring(
+    trigger: Trigger<OnRemove, Spring>,
+    springs: Query<&Spring>,
+    mut pending: ResMut<PendingWakes>,
+) {
+    if let Ok(spring) = springs.get(trigger.target()) {
+        pending.points.extend([spring.point1, spring.point2]);
     }
 }
 
-fn converge(
-    mut point_query: Query<&mut Point>,
-    stick_query: Query<&Stick>,
-    mut state: ResMut<FrameComparison>,
-    sim_settings: Res<SimulationSettings>,
+fn record_removed_point(
+    trigger: Trigger<OnRemove, Point>,
+    points: Query<&Point>,
+    mut pending: ResMut<PendingWakes>,
 ) {
-    for _ in 0..sim_settings.converge_iterations {
-        // first mutate all points
-        constrain_points(&mut point_query, &mut state, &sim_settings);
-        // then adjust sticks
-        restore_stick_constraints(&mut point_query, &stick_query, &mut state, &sim_settings);
+    if let Ok(point) = points.get(trigger.target()) {
+        pending.spheres.push((point.position, point.radius));
     }
 }
 
-fn constrain_points(
-    query: &mut Query<&mut Point>,
-    state: &mut ResMut<FrameComparison>,
-    sim_settings: &Res<SimulationSettings>,
+/// Copies the simulation points, sticks and springs into the `VerletWorld`, steps it once for every
+/// physics tick owed this frame and writes the results back onto the entities.
+fn simulate(
+    mut point_query: Query<(Entity, &mut Point)>,
+    mut bodies: BodyQueries,
+    mut pending_wakes: ResMut<PendingWakes>,
+    collider_query: Query<&Collider>,
+    gravity_zone_query: Query<&GravityZone>,
+    force_field_query: Query<&ForceField>,
+    time: Res<Time>,
+    mut commands: Commands,
+    mut stick_broken_writer: EventWriter<StickBroken>,
+    mut point_despawned_writer: EventWriter<PointDespawned>,
+    mut clock: ResMut<PhysicsClock>,
+    mut world: ResMut<VerletWorld>,
+    mut state: ResMut<FrameComparison>,
+    sim_settings: Res<SimulationSettings>,
 ) {
-    // Randomize the order in which points are updated
-    let mut points: Vec<_> = query.iter_mut().collect();
-    points.shuffle(&mut thread_rng());
+    let ticks = clock.advance(time.delta_secs(), &sim_settings);
+
+    // Keep the world in sync with any settings changed since the last frame
+    let reseed = world.settings.seed != sim_settings.seed;
+    world.settings = *sim_settings;
+    if reseed {
+        world.reseed();
+    }
 
     // Create a value to serve as the maximum distance change of all points.
     // This will be used to see if rendering needs to take place.
     let mut max_delta: f32 = 0.0;
 
-    for mut pt in points {
-        // Locked points should remain stationary
-        if pt.locked {
-            pt.prev_position = pt.position;
-            continue;
+    if ticks > 0 {
+        world.points.clear();
+        world.sticks.clear();
+        world.springs.clear();
+        world.muscles.clear();
+        world.kinematic_paths.clear();
+        world.bends.clear();
+        world.panels.clear();
+        world.pressures.clear();
+        world.shape_matches.clear();
+        world.colliders.clear();
+        world.colliders.extend(collider_query.iter().copied());
+        world.gravity_zones.clear();
+        world
+            .gravity_zones
+            .extend(gravity_zone_query.iter().copied());
+        world.force_fields.clear();
+        world.force_fields.extend(force_field_query.iter().copied());
+
+        // Map each point entity to its index in the world
+        let mut point_entities = Vec::new();
+        let mut point_indices = HashMap::new();
+        for (entity, point) in &point_query {
+            let index = world.add_point(*point);
+            if let Ok(path) = bodies.paths.get(entity) {
+                world.add_kinematic_path(index, path.clone());
+            }
+            point_indices.insert(entity, index);
+            point_entities.push(entity);
         }
-        // Store the current position to compare state change after update.
-        let previous_position = pt.position;
 
-        let velocity = pt.calculate_velocity();
+        let mut stick_entities = Vec::new();
+        for (entity, stick, muscle) in &bodies.sticks {
+            if let (Some(&p1), Some(&p2)) = (
+                point_indices.get(&stick.point1),
+                point_indices.get(&stick.point2),
+            ) {
+                world.sticks.push(stick.with_points(p1, p2));
+                if let Some(muscle) = muscle {
+                    let index = world.sticks.len() - 1;
+                    world.add_muscle(index, muscle.clone());
+                }
+                stick_entities.push(entity);
+            }
+        }
 
-        let half_width = sim_settings.simulation_bounds.x.1 * 0.5;
-        let half_depth = sim_settings.simulation_bounds.z.1 * 0.5;
-        let x_bounds_enabled = sim_settings.simulation_bounds.x.0;
-        let y_bounds_enabled = sim_settings.simulation_bounds.y.0;
-        let z_bounds_enabled = sim_settings.simulation_bounds.z.0;
+        for spring in &bodies.springs {
+            if let (Some(&p1), Some(&p2)) = (
+                point_indices.get(&spring.point1),
+                point_indices.get(&spring.point2),
+            ) {
+                world.springs.push(spring.with_points(p1, p2));
+            }
+        }
+
+        for bend in &bodies.bends {
+            if let (Some(&start), Some(&middle), Some(&end)) = (
+                point_indices.get(&bend.start),
+                point_indices.get(&bend.middle),
+                point_indices.get(&bend.end),
+            ) {
+                world.bends.push(bend.with_points(start, middle, end));
+            }
+        }
+
+        for panel in &bodies.panels {
+            if let (Some(&a), Some(&b), Some(&c)) = (
+                point_indices.get(&panel.a),
+                point_indices.get(&panel.b),
+                point_indices.get(&panel.c),
+            ) {
+                world.panels.push(panel.with_points(a, b, c));
+            }
+        }
 
-        let coef_restitution = sim_settings.coeff_restitution;
+        for pressure in &bodies.pressures {
+            if let Some(points) = pressure
+                .points
+                .iter()
+                .map(|point| point_indices.get(point).copied())
+                .collect::<Option<Vec<usize>>>()
+            {
+                world.pressures.push(pressure.with_points(points));
+            }
+        }
 
-        // Calculate bounce when the point hits the floor
-        if pt.position.y <= 0. && y_bounds_enabled {
-            // Bound the point to the floor
-            pt.position.y = 0.;
-            pt.prev_position.y = pt.position.y + velocity.y * coef_restitution;
+        let mut shape_match_entities = Vec::new();
+        for (entity, shape_match) in &bodies.shape_matches {
+            if let Some(points) = shape_match
+                .points
+                .iter()
+                .map(|point| point_indices.get(point).copied())
+                .collect::<Option<Vec<usize>>>()
+            {
+                world.shape_matches.push(shape_match.with_points(points));
+                shape_match_entities.push(entity);
+            }
         }
-        // Calculate bounce when the point hits the left wall
-        if pt.position.x <= -half_width && x_bounds_enabled {
-            // Bound the point to the wall
-            pt.position.x = -half_width;
-            pt.prev_position.x = pt.position.x + velocity.x * coef_restitution;
+
+        // Anything removed since the last tick may have cut a sleeping body loose
+        let cut_points: Vec<usize> = pending_wakes
+            .points
+            .drain(..)
+            .filter_map(|entity| point_indices.get(&entity).copied())
+            .collect();
+        let spheres = std::mem::take(&mut pending_wakes.spheres);
+        world.wake_islands_around(&cut_points, &spheres);
+
+        let mut despawned_points = HashMap::new();
+        for _ in 0..ticks {
+            max_delta = max_delta.max(world.step());
+
+            // Despawn the broken sticks, last first so the earlier indices stay valid
+            for broken in world.broken_sticks.iter().rev() {
+                let entity = stick_entities.remove(broken.index);
+                commands.entity(entity).despawn();
+
+                let _ = stick_broken_writer.write(StickBroken {
+                    point1: world.points[broken.stick.point1].position,
+                    point2: world.points[broken.stick.point2].position,
+                    strain: broken.strain,
+                });
+            }
+
+            for &(index, reason) in &world.despawned_points {
+                despawned_points.entry(index).or_insert(reason);
+            }
         }
-        // Calculate bounce when the point hits the right wall
-        else if pt.position.x >= half_width && x_bounds_enabled {
-            // Bound the point to the wall
-            pt.position.x = half_width;
-            pt.prev_position.x = pt.position.x + velocity.x * coef_restitution;
+
+        // Write the stepped points back onto their entities, only flagging the ones that moved
+        // as changed so resting scenes don't rebuild the `SpatialIndex` every frame
+        for (entity, point) in point_entities.iter().zip(world.points.iter()) {
+            if let Ok((_, mut ecs_point)) = point_query.get_mut(*entity) {
+                if ecs_point.position == point.position {
+                    *ecs_point.bypass_change_detection() = *point;
+                } else {
+                    *ecs_point = *point;
+                }
+            }
         }
-        // Flip the Z travel of going beyond some bound
-        if pt.position.z <= -half_depth && z_bounds_enabled {
-            pt.position.z = -half_depth;
-            pt.prev_position.z = pt.position.z + velocity.z * coef_restitution;
-        } else if pt.position.z > half_depth && z_bounds_enabled {
-            pt.position.z = half_depth;
-            pt.prev_position.z = pt.position.z + velocity.z * coef_restitution;
+
+        // Carry the matched rotations over so the next frame's search starts from them
+        for (entity, shape_match) in shape_match_entities.iter().zip(world.shape_matches.iter()) {
+            if let Ok((_, mut ecs_shape_match)) = bodies.shape_matches.get_mut(*entity) {
+                ecs_shape_match.rotation = shape_match.rotation;
+            }
         }
 
-        // Compare the distances before and after updated to see how it compares to the max_delta seen so far.
-        let updated_position = pt.position;
-        let delta = updated_position.distance(previous_position).abs();
-        if delta > max_delta && !state.changed {
-            // Update max_delta if this value is greater.
-            max_delta = delta;
+        // Any sticks, springs, bends or shapes left without the point are removed by `despawn_orphans`
+        for (index, reason) in despawned_points {
+            commands.entity(point_entities[index]).despawn();
+
+            let _ = point_despawned_writer.write(PointDespawned {
+                position: world.points[index].position,
+                reason,
+            });
         }
     }
 
-    // No further checks required if the frame is already going to change.
-    if state.changed {
-        return;
-    }
     if max_delta > sim_settings.min_render_delta {
         // Reflect the in the state that this frame needs to be rendered.
         state.frames_since = 0;
         state.changed = true;
+    } else if state.frames_since > sim_settings.max_unchanged_frames {
+        // Check if the frame should be rendered based on reaching it's frame limit.
+        state.frames_since = 0;
+        state.changed = true;
     } else {
         // If no changes happened to dictate a rerender, make it known.
+        state.frames_since += 1;
         state.changed = false;
     }
 }
 
-fn restore_stick_constraints(
-    point_query: &mut Query<&mut Point>,
-    stick_query: &Query<&Stick>,
-    state: &mut ResMut<FrameComparison>,
-    sim_settings: &Res<SimulationSettings>,
+/// Listens for any spawn requst sent from Leptos and inserts the mesh_network into
+/// the spawn buffer for the next spawn cycle.
+fn handle_spawn_requests(
+    mut event_reader: EventReader<SpawnRequest>,
+    mut buffer: ResMut<SpawnBuffer>,
 ) {
-    // Create a value to serve as the maximum distance change of all points.
-    // This will be used to see if rendering needs to take place.
-    let mut max_delta: f32 = 0.0;
-
-    for stick in &mut stick_query.iter() {
-        if let Ok([mut p1, mut p2]) = point_query.get_many_mut([stick.point1, stick.point2]) {
-            // If both points are locked, skip computation
-            if p1.locked && p2.locked {
-                continue;
-            }
-
-            // Store the current position to compare state change after update.
-            let p1_previous_position = p1.position;
-            let p2_previous_position = p2.position;
-
-            let delta = p2.position - p1.position;
-            let current_len = delta.length();
-
-            let offset = delta * (current_len - stick.length) / current_len / 2.0;
-
-            // Locked points should remain stationary
-            // If one point is locked, apply double the offset to the other point
-            if p1.locked {
-                p2.position -= 2. * offset;
-            } else if p2.locked {
-                p1.position += 2. * offset;
-            } else {
-                // If neither point is locked, apply the offset evenly
-                p1.position += offset;
-                p2.position -= offset;
-            }
+    for event in event_reader.read() {
+        buffer.buffer.push(event.clone())
+    }
+}
+/// Spawn any mesh networks in the spawn buffer and clear out the buffer.
+fn spawn_buffer(
+    mut buffer: ResMut<SpawnBuffer>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for request in buffer.buffer.drain(..) {
+        let point_entities = spawner(
+            request.mesh_network.clone(),
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+        );
 
-            // Compare the distances before and after updated to see how it compares to the max_delta seen so far.
-            let p1_updated_position = p1.position;
-            let p2_updated_position = p2.position;
-            let delta1 = p1_updated_position.distance(p1_previous_position).abs();
-            let delta2 = p2_updated_position.distance(p2_previous_position).abs();
-            if delta1 > max_delta {
-                // Update max_delta if this value is greater.
-                max_delta = delta1;
-            }
-            if delta2 > max_delta {
-                // Update max_delta if this value is greater.
-                max_delta = delta2;
-            }
+        for pressure in &request.pressure {
+            spawn_pressure(
+                pressure,
+                &request.mesh_network,
+                &point_entities,
+                &mut commands,
+            );
+        }
+        for shape_match in &request.shape_match {
+            spawn_shape_match(
+                shape_match,
+                &request.mesh_network,
+                &point_entities,
+                &mut commands,
+            );
         }
     }
+}
 
-    // No further checks required if the frame is already going to change.
-    if state.changed {
+/// Rebuilds the `SpatialIndex` if any point or stick was added, moved or removed since the last frame.
+fn index_entities(
+    mut index: ResMut<SpatialIndex>,
+    point_query: Query<(Entity, &Point)>,
+    stick_query: Query<(Entity, &Stick)>,
+    changed_points: Query<(), Changed<Point>>,
+    changed_sticks: Query<(), Changed<Stick>>,
+    mut removed_points: RemovedComponents<Point>,
+    mut removed_sticks: RemovedComponents<Stick>,
+    sim_settings: Res<SimulationSettings>,
+) {
+    let removed = removed_points.read().count() + removed_sticks.read().count() > 0;
+    if changed_points.is_empty() && changed_sticks.is_empty() && !removed {
         return;
     }
-    if max_delta > sim_settings.min_render_delta {
-        // Reflect the in the state that this frame needs to be rendered.
-        state.frames_since = 0;
-        state.changed = true;
-    } else {
-        // If no changes happened to dictate a rerender, make it known.
-        state.changed = false;
+
+    let index = &mut *index;
+    let cell_size = sim_settings.spatial_hash_cell_size;
+
+    index.points.reset(cell_size);
+    index.point_entities.clear();
+    for (entity, point) in &point_query {
+        index
+            .points
+            .insert_sphere(index.point_entities.len(), point.position, point.radius);
+        index.point_entities.push(entity);
+    }
+
+    index.sticks.reset(cell_size);
+    index.stick_entities.clear();
+    for (entity, stick) in &stick_query {
+        if let Ok([p1, p2]) = point_query.get_many([stick.point1, stick.point2]) {
+            index.sticks.insert_segment(
+                index.stick_entities.len(),
+                p1.1.position,
+                p2.1.position,
+                stick.radius,
+            );
+            index.stick_entities.push(entity);
+        }
     }
 }
 
-fn filter(mut q: Query<&mut Point>, sim_settings: Res<SimulationSettings>) {
-    // how aggressively to shave off the discrete acceleration “spike”
-    let jerk_damp: f32 = sim_settings.jerk_damping;
+/// Despawns any stick, spring, bend, panel, pressure or shape match left without its points.
+fn despawn_orphans(
+    stick_query: Query<(Entity, &Stick)>,
+    spring_query: Query<(Entity, &Spring)>,
+    bend_query: Query<(Entity, &Bend)>,
+    panel_query: Query<(Entity, &Panel)>,
+    pressure_query: Query<(Entity, &Pressure)>,
+    shape_match_query: Query<(Entity, &ShapeMatch)>,
+    point_query: Query<(Entity, &Point)>,
+    mut commands: Commands,
+) {
+    // Remove any sticks and springs left without their points
+    for (stick_entity, stick) in &stick_query {
+        if point_query.get_many([stick.point1, stick.point2]).is_err() {
+            commands.entity(stick_entity).despawn();
+        }
+    }
+    for (spring_entity, spring) in &spring_query {
+        if point_query
+            .get_many([spring.point1, spring.point2])
+            .is_err()
+        {
+            commands.entity(spring_entity).despawn();
+        }
+    }
 
-    for mut pt in q.iter_mut() {
-        if pt.locked {
-            continue;
+    // Remove any bends left without their points
+    for (bend_entity, bend) in &bend_query {
+        if point_query
+            .get_many([bend.start, bend.middle, bend.end])
+            .is_err()
+        {
+            commands.entity(bend_entity).despawn();
         }
+    }
 
-        // t–1 -> the previous position
-        let p_nm1 = pt.previously_rendered_position;
-        // t -> the current position
-        let p_n = pt.prev_position;
-        // t+1 -> The next position
-        let p_np1 = pt.position;
+    // Remove any panels left without their points
+    for (panel_entity, panel) in &panel_query {
+        if point_query.get_many([panel.a, panel.b, panel.c]).is_err() {
+            commands.entity(panel_entity).despawn();
+        }
+    }
 
-        // compute the second finite difference:
-        let delta2 = p_np1 - 2.0 * p_n + p_nm1;
-        // subtract off a fraction of it:
-        let damped = p_np1 - delta2 * jerk_damp;
+    // Remove any pressures that lost one of their points
+    for (pressure_entity, pressure) in &pressure_query {
+        if pressure
+            .points
+            .iter()
+            .any(|point| !point_query.contains(*point))
+        {
+            commands.entity(pressure_entity).despawn();
+        }
+    }
 
-        pt.position = damped;
+    // Remove any shape matches that lost one of their points
+    for (shape_match_entity, shape_match) in &shape_match_query {
+        if shape_match
+            .points
+            .iter()
+            .any(|point| !point_query.contains(*point))
+        {
+            commands.entity(shape_match_entity).despawn();
+        }
     }
 }